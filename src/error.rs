@@ -0,0 +1,262 @@
+use std::fmt::{Display, Formatter};
+use std::io;
+
+pub type Result<T> = std::result::Result<T, ArkError>;
+
+/// The object being decoded when an error occurred.
+#[derive(Debug)]
+pub struct ObjectContext {
+    pub index: usize,
+    pub class: String,
+}
+
+#[derive(Debug)]
+pub enum ArkError {
+    Io(io::Error),
+    UnexpectedEof {
+        offset: u64,
+        field: &'static str,
+    },
+    UnsupportedVersion {
+        offset: u64,
+        version: i16,
+    },
+    InvalidHeader {
+        offset: u64,
+        message: String,
+    },
+    InvalidOffset {
+        offset: u64,
+        target: u64,
+    },
+    InvalidString {
+        offset: u64,
+        message: String,
+    },
+    InvalidData {
+        offset: u64,
+        message: String,
+    },
+    UnknownPropertyType {
+        offset: u64,
+        type_name: String,
+    },
+    UnknownObjectReference {
+        offset: u64,
+        kind: i32,
+    },
+    /// A value that can't be stored in a property of the given type.
+    TypeMismatch {
+        type_name: String,
+        value: String,
+    },
+    /// An edit to an index of a property the object doesn't have.
+    MissingProperty {
+        ind: u32,
+    },
+    /// A name that isn't in the save's names table.
+    UnknownName {
+        name: String,
+    },
+    /// An object index that can't be edited, because it's past the end of the
+    /// object list.
+    UnknownObject {
+        index: usize,
+    },
+    /// An edit while the objects are still shared, such as with an `Entry`
+    /// kept from before.
+    ObjectsInUse,
+    /// Breadcrumbs describing where in the save `source` was raised.
+    Context {
+        object: Option<ObjectContext>,
+        path: Vec<String>,
+        source: Box<ArkError>,
+    },
+}
+
+impl ArkError {
+    /// The file offset the error was raised at, if known.
+    pub fn offset(&self) -> Option<u64> {
+        match self {
+            ArkError::Io(_)
+            | ArkError::TypeMismatch { .. }
+            | ArkError::MissingProperty { .. }
+            | ArkError::UnknownName { .. }
+            | ArkError::UnknownObject { .. }
+            | ArkError::ObjectsInUse => None,
+            ArkError::UnexpectedEof { offset, .. }
+            | ArkError::UnsupportedVersion { offset, .. }
+            | ArkError::InvalidHeader { offset, .. }
+            | ArkError::InvalidOffset { offset, .. }
+            | ArkError::InvalidString { offset, .. }
+            | ArkError::InvalidData { offset, .. }
+            | ArkError::UnknownPropertyType { offset, .. }
+            | ArkError::UnknownObjectReference { offset, .. } => Some(*offset),
+            ArkError::Context { source, .. } => source.offset(),
+        }
+    }
+
+    /// The underlying error with any context stripped off.
+    pub fn root(&self) -> &ArkError {
+        match self {
+            ArkError::Context { source, .. } => source.root(),
+            _ => self,
+        }
+    }
+
+    pub fn object(&self) -> Option<&ObjectContext> {
+        match self {
+            ArkError::Context { object, .. } => object.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// Property names from the outermost property down to the one that failed.
+    pub fn path(&self) -> &[String] {
+        match self {
+            ArkError::Context { path, .. } => path,
+            _ => &[],
+        }
+    }
+
+    pub(crate) fn in_property(self, name: &str) -> Self {
+        match self {
+            ArkError::Context {
+                object,
+                mut path,
+                source,
+            } => {
+                path.insert(0, String::from(name));
+                ArkError::Context {
+                    object,
+                    path,
+                    source,
+                }
+            }
+            _ => ArkError::Context {
+                object: None,
+                path: vec![String::from(name)],
+                source: Box::new(self),
+            },
+        }
+    }
+
+    pub(crate) fn in_object(self, index: usize, class: &str) -> Self {
+        let context = ObjectContext {
+            index,
+            class: String::from(class),
+        };
+        match self {
+            ArkError::Context {
+                object: None,
+                path,
+                source,
+            } => ArkError::Context {
+                object: Some(context),
+                path,
+                source,
+            },
+            ArkError::Context { .. } => self,
+            _ => ArkError::Context {
+                object: Some(context),
+                path: Vec::new(),
+                source: Box::new(self),
+            },
+        }
+    }
+}
+
+impl Display for ArkError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            ArkError::Io(e) => write!(f, "{}", e),
+            ArkError::UnexpectedEof { offset, field } => write!(
+                f,
+                "Unexpected end of data reading {} at offset {}",
+                field, offset
+            ),
+            ArkError::UnsupportedVersion { offset, version } => write!(
+                f,
+                "Unsupported file version {} at offset {}",
+                version, offset
+            ),
+            ArkError::InvalidHeader { offset, message } => {
+                write!(f, "Invalid header at offset {}: {}", offset, message)
+            }
+            ArkError::InvalidOffset { offset, target } => write!(
+                f,
+                "Offset {} read at {} is outside the file",
+                target, offset
+            ),
+            ArkError::InvalidString { offset, message } => {
+                write!(f, "Invalid string at offset {}: {}", offset, message)
+            }
+            ArkError::InvalidData { offset, message } => {
+                write!(f, "Invalid data at offset {}: {}", offset, message)
+            }
+            ArkError::UnknownPropertyType { offset, type_name } => write!(
+                f,
+                "Unknown property type {} at offset {}",
+                type_name, offset
+            ),
+            ArkError::UnknownObjectReference { offset, kind } => write!(
+                f,
+                "Unknown object reference type {} at offset {}",
+                kind, offset
+            ),
+            ArkError::TypeMismatch { type_name, value } => {
+                write!(f, "A {} can't hold the value {}", type_name, value)
+            }
+            ArkError::MissingProperty { ind } => write!(f, "No property with index {}", ind),
+            ArkError::UnknownName { name } => write!(f, "Names table has no {}", name),
+            ArkError::UnknownObject { index } => {
+                write!(f, "Object {} isn't in the object list", index)
+            }
+            ArkError::ObjectsInUse => write!(f, "Objects can't be edited while they are shared"),
+            ArkError::Context {
+                object,
+                path,
+                source,
+            } => {
+                write!(f, "{}", source)?;
+                if let Some(object) = object {
+                    write!(f, " in object {} ({})", object.index, object.class)?;
+                }
+                if !path.is_empty() {
+                    write!(f, " at property {}", path.join("."))?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for ArkError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ArkError::Io(e) => Some(e),
+            ArkError::Context { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ArkError {
+    fn from(e: io::Error) -> Self {
+        ArkError::Io(e)
+    }
+}
+
+impl From<ArkError> for io::Error {
+    fn from(e: ArkError) -> Self {
+        let kind = match e.root() {
+            ArkError::Io(e) => e.kind(),
+            ArkError::UnexpectedEof { .. } => io::ErrorKind::UnexpectedEof,
+            _ => io::ErrorKind::InvalidData,
+        };
+        match e {
+            ArkError::Io(e) => e,
+            _ => io::Error::new(kind, e),
+        }
+    }
+}
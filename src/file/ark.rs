@@ -1,49 +1,124 @@
-use crate::io::{ArrayReader, Reader};
-use crate::object::{Names, Object};
-use crate::properties::{Properties, Value};
+use crate::io::{ArrayReader, Owned, Reader, Source};
+use crate::object::{Names, Object, ObjectRecord};
+use crate::properties::Properties;
+use crate::{ArkError, Result};
 use crate::{Entry, Location};
-use std::fs::File;
-use std::io::{Error, ErrorKind};
-use std::io::{Result, SeekFrom, Write};
+use std::borrow::Cow;
+use std::convert::TryFrom;
+use std::io::SeekFrom;
 use std::rc::Rc;
 
-use super::CryopodParser;
+use super::data_files::{map_name, read_data_files, read_data_files_object_map};
+use super::embedded::read_embedded_data;
+use super::hibernation::read_hibernation;
+use super::{CreatureContainers, DataFileObjects, EmbeddedData, SaveHeader};
 
-pub struct ArkParser {
-    _class_offset: u64,
-    pub names: Rc<Names>,
-    entries: Vec<Entry>,
+pub struct ArkParser<'a> {
+    pub header: SaveHeader,
+    pub embedded_data: Vec<EmbeddedData>,
+    pub names: Rc<Names<'a>>,
+    pub(crate) entries: Vec<Entry<'a>>,
+    /// The first of `data_files`, which is usually the map.
     pub map: String,
+    /// The human readable name of the map, from its persistent level.
+    pub map_name: String,
+    /// The persistent level followed by any streamed sub-levels.
+    pub data_files: Vec<String>,
+    pub data_files_object_map: Vec<DataFileObjects>,
+    pub(crate) objects: Rc<Vec<Object<'a>>>,
+    /// Number of objects in the object list, which come before any read from
+    /// hibernation or creature containers.
+    pub(crate) object_count: usize,
+    pub(crate) sections: RawSections<'a>,
+    /// Why the payloads of any creature containers couldn't be read. Their
+    /// creatures are left out rather than failing the whole save.
+    pub container_errors: Vec<ArkError>,
 }
 
-impl ArkParser {
+/// Parts of the save kept as read, so it can be written back.
+pub(crate) struct RawSections<'a> {
+    /// From the end of the object list to the names table, which holds the
+    /// hibernation section.
+    pub after_objects: Cow<'a, [u8]>,
+    /// Where the hibernation section starts in `after_objects`.
+    pub hibernation: Option<u64>,
+    /// From the end of the names table to the first property block.
+    pub after_names: Cow<'a, [u8]>,
+    /// Anything after the last property block.
+    pub trailer: Cow<'a, [u8]>,
+}
+
+impl ArkParser<'static> {
+    /// Parses a save, copying every name and string out of `file` into its
+    /// own allocation. To borrow them instead, map or read the whole save
+    /// into memory and use `read_borrowed`.
     pub fn read(file: &mut dyn Reader) -> Result<Self> {
-        let (_version, names_offset, properties_offset) = read_header(file)?;
-        let map = skip_binary_data_names(file)?;
-        skip_embedded_binary_data(file)?;
-        skip_data_files_object_map(file)?;
-        let names = Rc::new(Names::new(file, names_offset)?);
-        let mut objects = read_objects(file, &names, properties_offset)?;
-        let mut frozen_dinos = uncryopod_dinos(&objects, &names);
-        objects.append(&mut frozen_dinos);
-        let objects = Rc::new(objects);
-        let entries = objects
-            .iter()
-            .enumerate()
-            .map(|(i, o)| Entry {
-                object_type: o.object_type,
-                objects: objects.clone(),
-                object: i,
-                inventory: o.inventory_component,
-                status: o.status_component,
-            })
+        ArkParser::read_with(file, &CreatureContainers::default())
+    }
+
+    /// Parses a save, extracting creatures from the given `containers`. Like
+    /// `read`, this copies every name and string.
+    pub fn read_with(file: &mut dyn Reader, containers: &CreatureContainers) -> Result<Self> {
+        ArkParser::parse(&mut Owned(file), containers)
+    }
+}
+
+impl<'a> ArkParser<'a> {
+    /// Parses a save held in memory, such as `MMappedReader::as_slice`,
+    /// borrowing names and strings from `data` rather than copying them.
+    pub fn read_borrowed(data: &'a [u8]) -> Result<Self> {
+        ArkParser::read_borrowed_with(data, &CreatureContainers::default())
+    }
+
+    /// Parses a save held in memory, extracting creatures from the given
+    /// `containers`.
+    pub fn read_borrowed_with(data: &'a [u8], containers: &CreatureContainers) -> Result<Self> {
+        ArkParser::parse(&mut ArrayReader::new(data), containers)
+    }
+
+    fn parse(file: &mut dyn Source<'a>, containers: &CreatureContainers) -> Result<Self> {
+        let file_size = file.seek(SeekFrom::End(0))?;
+        file.seek(SeekFrom::Start(0))?;
+        let header = SaveHeader::read(file, file_size)?;
+        let data_files = read_data_files(file)?;
+        let embedded_data = read_embedded_data(file)?;
+        let data_files_object_map = read_data_files_object_map(file)?;
+        let map = data_files.first().cloned().unwrap_or_default();
+        let map_name = map_name(&data_files, &data_files_object_map);
+        let mut names = Names::new(file, header.names_offset)?;
+        let (mut records, properties_end) =
+            read_objects(file, &mut names, header.properties_offset, file_size)?;
+        let object_count = records.len();
+        let sections = read_sections(file, &header, &names, properties_end, file_size)?;
+        let mut hibernated = read_hibernation(file, &header, &mut names, records.len())?;
+        records.append(&mut hibernated);
+        let (mut frozen_dinos, container_errors) = containers.extract(&records, &mut names);
+        records.append(&mut frozen_dinos);
+
+        let names = Rc::new(names);
+        let objects: Rc<Vec<Object>> = Rc::new(
+            records
+                .into_iter()
+                .map(|record| record.into_object(&names))
+                .collect(),
+        );
+        let entries = (0..objects.len())
+            .map(|i| Entry::new(&objects, i))
             .collect();
 
         Ok(ArkParser {
-            _class_offset: names_offset,
+            header,
+            embedded_data,
             names,
             entries,
             map,
+            map_name,
+            data_files,
+            data_files_object_map,
+            objects,
+            object_count,
+            sections,
+            container_errors,
         })
     }
 
@@ -55,28 +130,48 @@ impl ArkParser {
         self.names.get_name_id(name)
     }
 
-    pub fn entries(&self) -> &Vec<Entry> {
+    /// The streamed sub-level an object was saved in, looked up by its name
+    /// including the instance suffix, such as `Dodo_Character_BP_C_12`.
+    pub fn sub_level(&self, object_name: &str) -> Option<&str> {
+        let level = self
+            .data_files_object_map
+            .iter()
+            .find(|l| l.objects.iter().any(|o| o == object_name))?;
+        self.data_files
+            .get(level.level as usize)
+            .map(String::as_str)
+    }
+
+    pub fn entries(&self) -> &Vec<Entry<'a>> {
         &self.entries
     }
 }
 
-pub fn read_objects<'a>(
-    file: &mut dyn Reader,
-    names: &Rc<Names>,
+/// Reads an object list, returning the objects and where the last of their
+/// property blocks ends. The file is left at the end of the list.
+pub(crate) fn read_objects<'a>(
+    file: &mut dyn Source<'a>,
+    names: &mut Names<'a>,
     properties_offset: u64,
-) -> Result<Vec<Object>> {
+    end: u64,
+) -> Result<(Vec<ObjectRecord<'a>>, u64)> {
     let object_count = file.read_i32()?;
-    let mut objects = Vec::with_capacity(object_count as usize);
-    for _ in 0..object_count {
+    // Each object takes at least its guid, so the count can't exceed what's left
+    let remaining = end.saturating_sub(file.stream_position()?) / 16;
+    let mut objects = Vec::with_capacity((object_count.max(0) as u64).min(remaining) as usize);
+    let mut properties_end = properties_offset;
+    for index in 0..object_count {
         let guid = file.read_u128()?;
-        let name = file.read_name()?;
+        let name = names.read_name(file)?;
         let is_item = file.read_bool()?;
 
         let extra_class_count = file.read_i32()?;
+        let mut extra_names = Vec::with_capacity((extra_class_count.max(0) as usize).min(4096));
         for _ in 0..extra_class_count {
-            file.read_name()?;
+            extra_names.push(names.read_name(file)?);
         }
-        file.seek(SeekFrom::Current(8))?;
+        let from_data_file = file.read_i32()?;
+        let data_file_index = file.read_i32()?;
 
         let location = if file.read_bool()? {
             Some(Location::read(file)?)
@@ -84,135 +179,189 @@ pub fn read_objects<'a>(
             None
         };
 
-        let object_properties_offset = file.read_i32()? as u64;
+        let object_properties_offset = file.read_i32()?;
         let mut properties = Properties::new();
-        let next_object = file.seek(SeekFrom::Current(4))?;
+        let unknown = file.read_i32()?;
+        let next_object = file.stream_position()?;
 
-        file.seek(SeekFrom::Start(
-            properties_offset + object_properties_offset,
-        ))?;
-        properties.read(file, names)?;
+        let target = u64::try_from(object_properties_offset)
+            .ok()
+            .and_then(|offset| properties_offset.checked_add(offset))
+            .ok_or_else(|| {
+                ArkError::InvalidData {
+                    offset: next_object - 8,
+                    message: format!("Invalid properties offset {}", object_properties_offset),
+                }
+                .in_object(index as usize, &names[name.id])
+            })?;
+        if target >= end {
+            return Err(ArkError::InvalidOffset {
+                offset: next_object - 8,
+                target,
+            }
+            .in_object(index as usize, &names[name.id]));
+        }
+        file.seek(SeekFrom::Start(target))?;
+        properties
+            .read(file, names)
+            .map_err(|e| e.in_object(index as usize, &names[name.id]))?;
+        properties_end = properties_end.max(file.stream_position()?);
         file.seek(SeekFrom::Start(next_object))?;
 
-        objects.push(Object::new(
+        objects.push(ObjectRecord {
             guid,
             name,
             is_item,
+            extra_names,
+            from_data_file,
+            data_file_index,
             location,
             properties,
-            names.clone(),
-        ));
+            unknown,
+            hibernation: None,
+            cryopod: None,
+            first_index: 0,
+        });
     }
-    Ok(objects)
+    Ok((objects, properties_end))
 }
 
-fn read_header(file: &mut dyn Reader) -> Result<(i16, u64, u64)> {
-    let version = file.read_i16()?;
-    if version < 5 || version > 9 {
-        return Err(Error::new(
-            ErrorKind::InvalidData,
-            format!("Unsupported file version {}", version),
-        ));
-    }
-
-    if version > 6 {
-        let _hibernation_offset = file.read_u32()? as u64;
-        let zero_value = file.read_i32()?;
-        if zero_value != 0 {
-            return Err(Error::new(
-                ErrorKind::InvalidData,
-                "Found non zero value in header",
-            ));
-        }
+/// Reads the sections around the names table and property blocks that
+/// aren't otherwise kept, starting at the end of the object list.
+fn read_sections<'a>(
+    file: &mut dyn Source<'a>,
+    header: &SaveHeader,
+    names: &Names,
+    properties_end: u64,
+    file_size: u64,
+) -> Result<RawSections<'a>> {
+    let objects_end = file.stream_position()?;
+    let after_objects = file.read_data(header.names_offset.saturating_sub(objects_end) as usize)?;
+    let hibernation = header
+        .hibernation_offset
+        .filter(|offset| (objects_end..=header.names_offset).contains(offset))
+        .map(|offset| offset - objects_end);
+    file.seek(SeekFrom::Start(names.table_end))?;
+    let after_names =
+        file.read_data(header.properties_offset.saturating_sub(names.table_end) as usize)?;
+    file.seek(SeekFrom::Start(properties_end))?;
+    let trailer = file.read_data(file_size.saturating_sub(properties_end) as usize)?;
+    Ok(RawSections {
+        after_objects,
+        hibernation,
+        after_names,
+        trailer,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::{name, save};
+    use crate::io::Writer;
+
+    fn position(data: &[u8], pattern: &[u8]) -> usize {
+        data.windows(pattern.len())
+            .position(|w| w == pattern)
+            .unwrap()
     }
 
-    let names_offset = file.read_u32()? as u64;
-    let properties_offset = file.read_i32()? as u64;
-    let _game_time = file.read_f32();
+    fn error(data: &[u8]) -> ArkError {
+        match ArkParser::read_borrowed(data) {
+            Ok(_) => panic!("A corrupt save was read"),
+            Err(e) => e,
+        }
+    }
 
-    if version > 8 {
-        let _save_count = file.read_u32()?;
+    fn patch(data: &[u8], at: usize, value: i32) -> Vec<u8> {
+        let mut data = data.to_vec();
+        data[at..at + 4].copy_from_slice(&value.to_le_bytes());
+        data
     }
 
-    // let classes_offset = file.read_i32()? as u64;
-    // file.seek(SeekFrom::Current(8))?;
-    Ok((version, names_offset, properties_offset))
-}
+    #[test]
+    fn data_files() {
+        let data = save();
+        let parser = ArkParser::read_borrowed(&data).unwrap();
+        assert_eq!(parser.map, "TheIsland");
+        assert_eq!(parser.map_name, "TheIsland");
+        assert_eq!(parser.data_files, vec!["TheIsland", "TheIsland_Caves"]);
+        assert_eq!(
+            parser.sub_level("Dodo_Character_BP_C_1"),
+            Some("TheIsland_Caves")
+        );
+        assert_eq!(parser.sub_level("Dodo_Character_BP_C_2"), None);
+        assert_eq!(parser.embedded_data[0].path, "/Game/Canvas.Canvas");
+    }
 
-fn skip_binary_data_names(file: &mut dyn Reader) -> Result<String> {
-    let count = file.read_i32()?;
-    let map = file.read_str()?;
-    for _ in 1..count {
-        file.skip_str()?;
+    #[test]
+    fn truncated() {
+        let data = save();
+        for len in (0..data.len()).step_by(7) {
+            assert!(ArkParser::read_borrowed(&data[..len]).is_err());
+            assert!(ArkParser::read(&mut std::io::Cursor::new(&data[..len])).is_err());
+        }
     }
-    Ok(map)
-}
 
-fn skip_embedded_binary_data(file: &mut dyn Reader) -> Result<()> {
-    let data_count = file.read_i32()?;
-    for _ in 0..data_count {
-        file.skip_str()?; //Path
-        let parts = file.read_i32()?;
-        for _ in 0..parts {
-            let blobs = file.read_i32()?;
-            for _ in 0..blobs {
-                let blob_size = file.read_i32()? * 4;
-                file.seek(SeekFrom::Current(blob_size as i64))?;
+    #[test]
+    fn corrupt_counts() {
+        let data = save();
+        let objects = position(&data, &0x1234u128.to_le_bytes()) - 4;
+        let names = 10;
+        let stats = position(&data, &[3, 0, 0, 0, 4, 0, 0, 0, 5, 0, 0, 0]);
+        // Counts of the data files, embedded data, its parts and their
+        // blobs, the object map and an object's extra names
+        let data_files = 26;
+        let embedded = position(&data, b"/Game/Canvas") - 8;
+        let (parts, blobs) = (embedded + 28, embedded + 32);
+        let object_map = position(&data, b"Dodo_Character_BP_C_1") - 16;
+        let extra_names = objects + 4 + 16 + 8 + 4;
+        let counts = [data_files, embedded, parts, blobs, object_map, extra_names];
+        for (at, count) in counts.iter().zip(&[2, 1, 1, 1, 1, 1]) {
+            assert_eq!(data[*at..*at + 4], i32::to_le_bytes(*count));
+        }
+        for at in [objects, names, stats].iter().chain(&counts) {
+            for count in &[-1, i32::MIN] {
+                // Negative counts are read as empty, which may leave the rest
+                // of the save unreadable, but must not panic
+                let _ = ArkParser::read_borrowed(&patch(&data, *at, *count));
             }
+            assert!(ArkParser::read_borrowed(&patch(&data, *at, i32::MAX)).is_err());
         }
     }
-    Ok(())
-}
 
-fn skip_data_files_object_map(file: &mut dyn Reader) -> Result<()> {
-    let entries = file.read_i32()?;
-    for _ in 0..entries {
-        file.seek(SeekFrom::Current(4 as i64))?;
-        let count = file.read_u32()?;
-        for _ in 0..count {
-            file.skip_str()?;
+    #[test]
+    fn corrupt_offsets() {
+        let data = save();
+        let object = position(&data, &0x1234u128.to_le_bytes());
+        let error = error(&patch(&data, object + 76, -8));
+        assert!(matches!(error.root(), ArkError::InvalidData { .. }));
+        assert_eq!(error.object().unwrap().index, 0);
+
+        let properties_offset = 14;
+        for offset in &[-1, i32::MAX] {
+            assert!(ArkParser::read_borrowed(&patch(&data, properties_offset, *offset)).is_err());
         }
     }
-    Ok(())
-}
 
-fn uncryopod_dinos(objects: &Vec<Object>, names: &Rc<Names>) -> Vec<Object> {
-    let cryopod = names
-        .get_name_id("PrimalItem_WeaponEmptyCryopod_C")
-        .unwrap();
-    let custom_item_data = names.get_name_id("CustomItemDatas").unwrap();
-    let heirarchy: Vec<usize> = vec!["CustomDataBytes", "ByteArrays", "Bytes"]
-        .iter()
-        .map(|name| *names.get_name_id(name).or(Some(&0)).unwrap())
-        .collect();
-
-    // let frozen_dinos = objects
-    objects
-        .iter()
-        .filter(|o| o.name.id == *cryopod && o.properties.contains_id(custom_item_data))
-        .for_each(|o| {
-            let custom_data = o
-                .properties
-                .props
-                .get(custom_item_data)
-                .unwrap()
-                .first()
-                .unwrap();
-            let mut value = &custom_data.value;
-            for id in &heirarchy {
-                if let Value::Properties(properties) = value {
-                    value = &properties.props.get(&id).unwrap().first().unwrap().value;
-                }
-            }
-            if let Value::ArrayOfU8(data) = value {
-                let mut f = File::create("cryo.dat").unwrap();
-                f.write_all(&data.as_slice()).unwrap();
-                let mut frozen_data = ArrayReader::from(data);
-                let parser = CryopodParser::read(&mut frozen_data, names).unwrap();
-                let frozen_objects = parser.objects;
-                serde_json::to_writer_pretty(std::io::stderr(), &frozen_objects).unwrap();
-            }
-        });
-    // .collect();
-    Vec::new()
+    #[test]
+    fn error_context() {
+        let data = save();
+        let mut owner = Vec::new();
+        owner.write_name(&name("Owner", 0)).unwrap();
+        // Cut the second object's properties inside the value of the
+        // struct's only property
+        let second = data.windows(8).rposition(|w| w == &owner[..]).unwrap();
+        let error = error(&data[..second + 24 + 8 + 24 + 2]);
+        assert!(matches!(error.root(), ArkError::UnexpectedEof { .. }));
+        let object = error.object().unwrap();
+        assert_eq!(
+            (object.index, object.class.as_str()),
+            (1, "Dodo_Character_BP_C")
+        );
+        assert_eq!(error.path(), &["Owner", "TargetingTeam"]);
+        assert!(error
+            .to_string()
+            .contains("in object 1 (Dodo_Character_BP_C) at property Owner.TargetingTeam"));
+    }
 }
@@ -0,0 +1,327 @@
+//! Ark: Survival Ascended saves, an SQLite database holding each object in
+//! the `game` table keyed by its guid and the header, with the names table,
+//! in the `custom` table.
+//!
+//! Objects and properties use the same layout as the original game with
+//! Unreal Engine 5 property tags. Object locations are stored separately, in
+//! the `ActorTransforms` row of the `custom` table.
+//!
+//! This is the layout of saves with version 13 in their `SaveHeader`. It has
+//! only been checked against the save written by `tests/fixtures/generate.py`
+//! from this description, not against one written by the game, so saves from
+//! other versions may not read.
+
+use super::data_files::map_name;
+use super::sqlite::{Column, Database};
+use crate::io::{ArrayReader, Name, Owned, Reader, Source};
+use crate::object::{Entry, Location, NameFormat, Names, Object, ObjectRecord, Rotator};
+use crate::properties::{Properties, Value};
+use crate::{ArkError, Result};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::convert::TryInto;
+use std::io::SeekFrom;
+use std::rc::Rc;
+
+pub struct AsaParser<'a> {
+    pub version: i16,
+    pub game_time: f64,
+    /// The human readable name of the map, from its persistent level.
+    pub map_name: String,
+    /// The persistent level followed by any streamed sub-levels.
+    pub data_files: Vec<String>,
+    pub names: Rc<Names<'a>>,
+    entries: Vec<Entry<'a>>,
+}
+
+impl AsaParser<'static> {
+    pub fn read(file: &mut dyn Reader) -> Result<Self> {
+        let mut data = Vec::new();
+        file.seek(SeekFrom::Start(0))?;
+        file.read_to_end(&mut data)?;
+        AsaParser::parse(&data)
+    }
+
+    fn parse(data: &[u8]) -> Result<Self> {
+        let database = Database::open(data)?;
+        let custom = database.table("custom")?;
+        let header =
+            custom_value(&custom, "SaveHeader").ok_or_else(|| ArkError::InvalidHeader {
+                offset: 0,
+                message: String::from("Save has no SaveHeader"),
+            })?;
+        let locations = match custom_value(&custom, "ActorTransforms") {
+            Some(transforms) => read_actor_transforms(transforms)?,
+            None => HashMap::new(),
+        };
+
+        let mut names = Names::with_strings();
+        let mut file = ArrayReader::new(header);
+        let version = file.read_i16()?;
+        let name_table_offset = file.read_i32()? as u64;
+        file.read_i32()?;
+        let game_time = file.read_f64()?;
+        let part_count = file.read_i32()?;
+        let mut data_files = Vec::with_capacity((part_count.max(0) as usize).min(4096));
+        for _ in 0..part_count {
+            data_files.push(file.read_str()?);
+            file.read_i32()?;
+        }
+        names.format = NameFormat::Mapped(read_names(&mut file, name_table_offset, &mut names)?);
+        names.asa = true;
+
+        let mut records = Vec::new();
+        for (index, row) in database.table("game")?.iter().enumerate() {
+            let (key, value) = match row.as_slice() {
+                [Column::Blob(key), Column::Blob(value), ..] if key.len() == 16 => (key, value),
+                _ => continue,
+            };
+            let guid = u128::from_le_bytes(key.as_slice().try_into().unwrap());
+            let mut file = ArrayReader::new(value);
+            let mut record = read_object(&mut Owned(&mut file), guid, &mut names, index)?;
+            record.location = locations.get(&guid).cloned();
+            records.push(record);
+        }
+        resolve_guids(&mut records, names.object_property);
+
+        let names = Rc::new(names);
+        let objects: Rc<Vec<Object>> = Rc::new(
+            records
+                .into_iter()
+                .map(|record| record.into_object(&names))
+                .collect(),
+        );
+        let entries = (0..objects.len())
+            .map(|i| Entry::new(&objects, i))
+            .collect();
+
+        Ok(AsaParser {
+            version,
+            game_time,
+            map_name: map_name(&data_files, &[]),
+            data_files,
+            names,
+            entries,
+        })
+    }
+}
+
+impl<'a> AsaParser<'a> {
+    pub fn get_name(&self, id: usize) -> &str {
+        &self.names[id]
+    }
+
+    pub fn get_name_id(&self, name: &str) -> Option<&usize> {
+        self.names.get_name_id(name)
+    }
+
+    pub fn entries(&self) -> &Vec<Entry<'a>> {
+        &self.entries
+    }
+}
+
+/// The value of a row in the `custom` table.
+fn custom_value<'c>(custom: &'c [Vec<Column>], name: &str) -> Option<&'c Vec<u8>> {
+    custom.iter().find_map(|row| match row.as_slice() {
+        [Column::Text(key), Column::Blob(value), ..] if key == name => Some(value),
+        _ => None,
+    })
+}
+
+/// Reads the locations of actors by guid. Each is saved as its guid, a
+/// location and rotation as doubles and eight unknown bytes, with an empty
+/// guid after the last one.
+fn read_actor_transforms(data: &[u8]) -> Result<HashMap<u128, Location>> {
+    let mut file = ArrayReader::new(data);
+    let mut locations = HashMap::new();
+    loop {
+        let guid = file.read_u128()?;
+        if guid == 0 {
+            return Ok(locations);
+        }
+        let location = Location {
+            x: file.read_f64()? as f32,
+            y: file.read_f64()? as f32,
+            z: file.read_f64()? as f32,
+            rotation: Rotator {
+                pitch: file.read_f64()? as f32,
+                yaw: file.read_f64()? as f32,
+                roll: file.read_f64()? as f32,
+            },
+        };
+        file.read_u64()?;
+        locations.insert(guid, location);
+    }
+}
+
+/// Reads the names table, keyed by arbitrary ids, into `names`.
+fn read_names(
+    file: &mut dyn Reader,
+    offset: u64,
+    names: &mut Names,
+) -> Result<HashMap<u32, usize>> {
+    file.seek(SeekFrom::Start(offset))?;
+    let count = file.read_i32()?;
+    let mut ids = HashMap::with_capacity((count.max(0) as usize).min(4096));
+    for _ in 0..count {
+        let key = file.read_u32()?;
+        let name = file.read_str()?;
+        ids.insert(key, names.intern(Cow::Owned(name)));
+    }
+    Ok(ids)
+}
+
+fn read_object<'a>(
+    file: &mut dyn Source<'a>,
+    guid: u128,
+    names: &mut Names<'a>,
+    index: usize,
+) -> Result<ObjectRecord<'a>> {
+    let name = names.read_name(file)?;
+    let unknown = file.read_i32()?;
+    let extra_count = file.read_i32()?;
+    let mut extra_names = Vec::with_capacity((extra_count.max(0) as usize).min(4096));
+    for _ in 0..extra_count {
+        extra_names.push(names.read_name(file)?);
+    }
+    let from_data_file = file.read_i32()?;
+    let data_file_index = file.read_i32()?;
+
+    let mut properties = Properties::new();
+    properties
+        .read(file, names)
+        .map_err(|e| e.in_object(index, &names[name.id]))?;
+
+    Ok(ObjectRecord {
+        guid,
+        name,
+        is_item: properties.contains("ItemID"),
+        extra_names,
+        from_data_file,
+        data_file_index,
+        location: None,
+        properties,
+        unknown,
+        hibernation: None,
+        cryopod: None,
+        first_index: 0,
+    })
+}
+
+/// Objects refer to each other by guid, which is replaced with the object's
+/// index to match the original game, so components can be found.
+fn resolve_guids(records: &mut [ObjectRecord], object_property: usize) {
+    let indexes: HashMap<u128, usize> = records
+        .iter()
+        .enumerate()
+        .map(|(i, record)| (record.guid, i))
+        .collect();
+    for record in records {
+        resolve_properties(&mut record.properties, &indexes, object_property);
+    }
+}
+
+/// Resolves the object properties in `properties`, including those held by
+/// structs, arrays, maps and sets.
+fn resolve_properties(
+    properties: &mut Properties,
+    indexes: &HashMap<u128, usize>,
+    object_property: usize,
+) {
+    let is_object = |name: Option<Name>| matches!(name, Some(name) if name.id == object_property);
+    for property in properties.props.values_mut().flatten() {
+        let (keys, values) = (
+            is_object(property.inner_type),
+            is_object(property.value_type),
+        );
+        match &mut property.value {
+            Value::Map(entries) => {
+                for (key, value) in entries {
+                    resolve_value(key, keys, indexes, object_property);
+                    resolve_value(value, values, indexes, object_property);
+                }
+            }
+            Value::Set(items) => {
+                for item in items {
+                    resolve_value(item, keys, indexes, object_property);
+                }
+            }
+            value => {
+                let is_object = property.type_name.id == object_property;
+                resolve_value(value, is_object, indexes, object_property);
+            }
+        }
+    }
+}
+
+fn resolve_value(
+    value: &mut Value,
+    is_object: bool,
+    indexes: &HashMap<u128, usize>,
+    object_property: usize,
+) {
+    match value {
+        // Guid structs hold guids too, but aren't references
+        Value::Guid(guid) if is_object => {
+            if let Some(index) = indexes.get(guid) {
+                *value = Value::Int(*index as i32);
+            }
+        }
+        Value::Properties(properties) => resolve_properties(properties, indexes, object_property),
+        Value::ArrayOfObject(items) => {
+            for item in items {
+                resolve_value(item, true, indexes, object_property);
+            }
+        }
+        Value::ArrayOfStruct(items) => {
+            for item in items {
+                resolve_value(item, false, indexes, object_property);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A dodo with a location and references to its status component, one
+    /// of them in a struct and another in an array, and the component, as
+    /// written by `tests/fixtures/generate.py`.
+    const SAVE: &[u8] = include_bytes!("../../tests/fixtures/asa.ark");
+
+    #[test]
+    fn objects() {
+        let parser = AsaParser::parse(SAVE).unwrap();
+        assert_eq!(parser.version, 13);
+        assert_eq!(parser.game_time, 4321.5);
+        assert_eq!(parser.data_files, vec!["TheIsland_WP"]);
+        assert_eq!(parser.map_name, "TheIsland");
+        let entries = parser.entries();
+        assert_eq!(entries.len(), 2);
+        let dodo = &entries[0];
+        assert_eq!(dodo.targeting_team(), Some(7));
+        let location = dodo.location().unwrap();
+        assert_eq!(
+            (location.x, location.y, location.z),
+            (100.5, -200.25, 300.0)
+        );
+        assert_eq!(location.rotation.yaw, 90.0);
+        assert!(entries[1].location().is_none());
+
+        let status = dodo.status_component().unwrap();
+        assert_eq!(
+            &parser.names[status.name.id],
+            "DinoCharacterStatusComponent_BP_C"
+        );
+        let id = |name| *parser.get_name_id(name).unwrap();
+        let owner = dodo.properties().get_properties(&id("Owner")).unwrap();
+        assert_eq!(owner.get_i32(&id("Target")), Some(1));
+        assert!(matches!(
+            dodo.properties().get(&id("Targets")),
+            Some(Value::ArrayOfObject(targets))
+                if matches!(targets.as_slice(), [Value::Int(1), Value::Int(0)])
+        ));
+    }
+}
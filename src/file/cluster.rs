@@ -0,0 +1,371 @@
+use super::profile::id;
+use super::CryopodParser;
+use super::PlayerProfile;
+use crate::io::{ArrayReader, Owned, Reader, Source};
+use crate::object::{Entry, Names, Object};
+use crate::properties::{Properties, Value};
+use crate::{ArkError, Result};
+use serde::Serialize;
+use std::rc::Rc;
+
+/// An item uploaded to the cluster.
+#[derive(Debug, Serialize)]
+pub struct UploadedItem {
+    /// Blueprint path of the item.
+    pub class: Option<String>,
+    pub custom_name: Option<String>,
+    pub quantity: i32,
+    pub is_blueprint: bool,
+    pub rating: f32,
+    pub upload_time: i32,
+}
+
+/// A character uploaded to the cluster.
+#[derive(Debug, Serialize)]
+pub struct UploadedCharacter {
+    pub name: Option<String>,
+    pub upload_time: i32,
+    /// The player data saved with the character, read like a profile.
+    pub profile: Option<PlayerProfile>,
+}
+
+/// A creature uploaded to the cluster, its objects decoded like a cryopod.
+pub struct UploadedCreature<'a> {
+    pub name: Option<String>,
+    pub upload_time: i32,
+    pub entry: Entry<'a>,
+}
+
+/// Parses the per player files in a cluster directory, which hold what the
+/// player has uploaded to an obelisk, supply drop or transmitter.
+pub struct ClusterParser<'a> {
+    pub version: i32,
+    pub names: Rc<Names<'a>>,
+    pub creatures: Vec<UploadedCreature<'a>>,
+    pub items: Vec<UploadedItem>,
+    pub characters: Vec<UploadedCharacter>,
+    /// Why uploaded creatures and characters that couldn't be read were
+    /// skipped.
+    pub upload_errors: Vec<ArkError>,
+}
+
+impl ClusterParser<'static> {
+    pub fn read(file: &mut dyn Reader) -> Result<Self> {
+        ClusterParser::parse(&mut Owned(file))
+    }
+}
+
+impl<'a> ClusterParser<'a> {
+    pub fn read_borrowed(data: &'a [u8]) -> Result<Self> {
+        ClusterParser::parse(&mut ArrayReader::new(data))
+    }
+
+    fn parse(file: &mut dyn Source<'a>) -> Result<Self> {
+        let version = file.read_i32()?;
+        let mut names = Names::with_strings();
+        let mut records = CryopodParser::read(file, &mut names)?.objects;
+
+        let ark_data = id(&names, "MyArkData");
+        let data = records
+            .iter()
+            .position(|o| o.properties.get_properties(&ark_data).is_some())
+            .ok_or_else(|| ArkError::InvalidData {
+                offset: 0,
+                message: String::from("Cluster file has no MyArkData"),
+            })?;
+
+        let mut uploads = Vec::new();
+        let mut creature_records = Vec::new();
+        let mut profiles = Vec::new();
+        let mut upload_errors = Vec::new();
+        {
+            let data = records[data].properties.get_properties(&ark_data).unwrap();
+            for character in data.get_structs(&id(&names, "ArkPlayerData")) {
+                let bytes = match character.get(&id(&names, "PlayerDataBytes")) {
+                    Some(Value::ArrayOfU8(bytes)) if !bytes.is_empty() => bytes,
+                    _ => {
+                        profiles.push(None);
+                        continue;
+                    }
+                };
+                // The same objects as a profile, without its version
+                let mut reader = ArrayReader::new(bytes);
+                match CryopodParser::read(&mut Owned(&mut reader), &mut names) {
+                    Ok(parsed) => profiles.push(PlayerProfile::find(&parsed.objects, &names)),
+                    Err(e) => {
+                        upload_errors.push(e.in_property("PlayerDataBytes"));
+                        profiles.push(None);
+                    }
+                }
+            }
+            for dino in data.get_structs(&id(&names, "ArkTamedDinosData")) {
+                let bytes = match dino.get(&id(&names, "DinoData")) {
+                    Some(Value::ArrayOfU8(bytes)) if !bytes.is_empty() => bytes.clone(),
+                    _ => continue,
+                };
+                let mut reader = ArrayReader::new(&bytes);
+                let parsed = match CryopodParser::read(&mut Owned(&mut reader), &mut names) {
+                    Ok(parsed) => parsed,
+                    Err(e) => {
+                        upload_errors.push(e.in_property("DinoData"));
+                        continue;
+                    }
+                };
+                let first_index = records.len() + creature_records.len();
+                uploads.push((
+                    string(dino, &names, "DinoName"),
+                    dino.get_i32(&id(&names, "UploadTime")).unwrap_or(0),
+                    first_index,
+                ));
+                for mut record in parsed.objects {
+                    record.first_index = first_index;
+                    creature_records.push(record);
+                }
+            }
+        }
+        records.append(&mut creature_records);
+
+        let names = Rc::new(names);
+        let objects: Rc<Vec<Object>> = Rc::new(
+            records
+                .into_iter()
+                .map(|record| record.into_object(&names))
+                .collect(),
+        );
+        let data = objects[data].properties.get_properties(&ark_data).unwrap();
+        let items = data
+            .get_structs(&id(&names, "ArkItems"))
+            .into_iter()
+            .map(|item| UploadedItem::new(item, &names))
+            .collect();
+        let characters = data
+            .get_structs(&id(&names, "ArkPlayerData"))
+            .into_iter()
+            .zip(profiles)
+            .map(|(character, profile)| UploadedCharacter {
+                name: string(character, &names, "PlayerName"),
+                upload_time: character.get_i32(&id(&names, "UploadTime")).unwrap_or(0),
+                profile,
+            })
+            .collect();
+        let creatures = uploads
+            .into_iter()
+            .map(|(name, upload_time, index)| UploadedCreature {
+                name,
+                upload_time,
+                entry: Entry::new(&objects, index),
+            })
+            .collect();
+
+        Ok(ClusterParser {
+            version,
+            names,
+            creatures,
+            items,
+            characters,
+            upload_errors,
+        })
+    }
+}
+
+impl UploadedItem {
+    fn new(upload: &Properties, names: &Names) -> Self {
+        let empty = Properties::new();
+        let item = upload
+            .get_properties(&id(names, "ArkTributeItem"))
+            .unwrap_or(&empty);
+        UploadedItem {
+            class: match item.get(&id(names, "ItemArchetype")) {
+                Some(Value::Name(name)) => Some(names.full_name(name).into_owned()),
+                _ => None,
+            },
+            custom_name: string(item, names, "CustomItemName"),
+            quantity: item.get_i32(&id(names, "ItemQuantity")).unwrap_or(1),
+            is_blueprint: item.get_bool(&id(names, "bIsBlueprint")).unwrap_or(false),
+            rating: item.get_f32(&id(names, "ItemRating")).unwrap_or(0.0),
+            upload_time: upload.get_i32(&id(names, "UploadTime")).unwrap_or(0),
+        }
+    }
+}
+
+fn string(data: &Properties, names: &Names, name: &str) -> Option<String> {
+    data.get_str(&id(names, name)).map(String::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::{player_data, str_name, str_objects, str_property};
+    use crate::io::Writer;
+
+    /// A tamed rex and its status component, as saved in `DinoData`.
+    fn rex() -> Vec<u8> {
+        let mut rex = Vec::new();
+        let name = str_name("Rexy");
+        str_property(&mut rex, "TamedName", "StrProperty", &[], &name);
+        str_property(&mut rex, "DinoID1", "IntProperty", &[], &[5, 0, 0, 0]);
+        let tamer = str_name("Us");
+        str_property(&mut rex, "TamerString", "StrProperty", &[], &tamer);
+        let status = "MyCharacterStatusComponent";
+        str_property(&mut rex, status, "IntProperty", &[], &[1, 0, 0, 0]);
+        rex.write_str("None").unwrap();
+        let mut status = Vec::new();
+        let level = "BaseCharacterLevel";
+        str_property(&mut status, level, "IntProperty", &[], &[150, 0, 0, 0]);
+        status.write_str("None").unwrap();
+        str_objects(
+            0,
+            &[
+                ("Rex_Character_BP_C", &rex),
+                ("DinoCharacterStatusComponent_BP_C", &status),
+            ],
+        )
+    }
+
+    fn bytes(file: &mut Vec<u8>, prop: &str, bytes: &[u8]) {
+        let mut data = Vec::new();
+        data.write_i32(bytes.len() as i32).unwrap();
+        data.write_bytes(bytes).unwrap();
+        let tag = str_name("ByteProperty");
+        str_property(file, prop, "ArrayProperty", &tag, &data);
+    }
+
+    fn upload_time(file: &mut Vec<u8>, time: i32) {
+        let time = time.to_le_bytes();
+        str_property(file, "UploadTime", "IntProperty", &[], &time);
+        file.write_str("None").unwrap();
+    }
+
+    /// A cluster file holding an item, the named creatures and a character
+    /// with its player data saved as `player`.
+    fn cluster(creatures: &[(&str, &[u8])], player: &[u8]) -> Vec<u8> {
+        let mut item = Vec::new();
+        let mut data = Vec::new();
+        data.write_i32(1).unwrap();
+        data.write_str("/Game/Items/PrimalItem_Metal.PrimalItem_Metal_C")
+            .unwrap();
+        str_property(&mut item, "ItemArchetype", "ObjectProperty", &[], &data);
+        let quantity = 100u32.to_le_bytes();
+        str_property(&mut item, "ItemQuantity", "UInt32Property", &[], &quantity);
+        str_property(&mut item, "bIsBlueprint", "BoolProperty", &[1], &[]);
+        item.write_str("None").unwrap();
+        let mut items = Vec::new();
+        items.write_i32(1).unwrap();
+        let tag = str_name("ItemNetInfo");
+        str_property(&mut items, "ArkTributeItem", "StructProperty", &tag, &item);
+        upload_time(&mut items, 111);
+
+        let mut dinos = Vec::new();
+        dinos.write_i32(creatures.len() as i32).unwrap();
+        for (name, data) in creatures {
+            let name = str_name(name);
+            str_property(&mut dinos, "DinoName", "StrProperty", &[], &name);
+            bytes(&mut dinos, "DinoData", data);
+            upload_time(&mut dinos, 222);
+        }
+
+        let mut characters = Vec::new();
+        characters.write_i32(1).unwrap();
+        let name = str_name("Bob");
+        str_property(&mut characters, "PlayerName", "StrProperty", &[], &name);
+        bytes(&mut characters, "PlayerDataBytes", player);
+        upload_time(&mut characters, 333);
+
+        let mut ark_data = Vec::new();
+        let tag = str_name("StructProperty");
+        str_property(&mut ark_data, "ArkItems", "ArrayProperty", &tag, &items);
+        let dinos_name = "ArkTamedDinosData";
+        str_property(&mut ark_data, dinos_name, "ArrayProperty", &tag, &dinos);
+        let players = "ArkPlayerData";
+        str_property(&mut ark_data, players, "ArrayProperty", &tag, &characters);
+        ark_data.write_str("None").unwrap();
+        let mut properties = Vec::new();
+        let tag = str_name("ArkInventoryData");
+        str_property(
+            &mut properties,
+            "MyArkData",
+            "StructProperty",
+            &tag,
+            &ark_data,
+        );
+        properties.write_str("None").unwrap();
+
+        let mut file = Vec::new();
+        file.write_i32(1).unwrap();
+        let objects = str_objects(4, &[("ArkCloudInventoryData", &properties)]);
+        file.write_bytes(&objects).unwrap();
+        file
+    }
+
+    fn player() -> Vec<u8> {
+        str_objects(0, &[("PrimalPlayerDataBP_C", &player_data())])
+    }
+
+    #[test]
+    fn uploads() {
+        let data = cluster(&[("Rexy - Lvl 150", &rex())], &player());
+        let cluster = ClusterParser::read_borrowed(&data).unwrap();
+        assert_eq!(cluster.version, 1);
+        assert!(cluster.upload_errors.is_empty());
+
+        let item = &cluster.items[0];
+        assert_eq!(
+            item.class.as_deref(),
+            Some("/Game/Items/PrimalItem_Metal.PrimalItem_Metal_C")
+        );
+        assert_eq!((item.quantity, item.upload_time), (100, 111));
+        assert!(item.is_blueprint);
+
+        let creature = &cluster.creatures[0];
+        assert_eq!(creature.name.as_deref(), Some("Rexy - Lvl 150"));
+        assert_eq!(creature.upload_time, 222);
+        let names = &cluster.names;
+        let dino = creature.entry.properties();
+        assert_eq!(dino.get_str(&id(names, "TamedName")), Some("Rexy"));
+        let status = creature.entry.status_component().unwrap();
+        let level = status.properties.get_i32(&id(names, "BaseCharacterLevel"));
+        assert_eq!(level, Some(150));
+
+        let character = &cluster.characters[0];
+        assert_eq!(character.name.as_deref(), Some("Bob"));
+        assert_eq!(character.upload_time, 333);
+        let profile = character.profile.as_ref().unwrap();
+        assert_eq!(profile.player_data_id, 987654321);
+        assert_eq!(profile.character_name.as_deref(), Some("Bob"));
+        assert_eq!(profile.tribe_id, Some(1500000001));
+        assert_eq!(profile.engrams.len(), 2);
+
+        let mut file = std::io::Cursor::new(&data);
+        let cluster = ClusterParser::read(&mut file).unwrap();
+        assert_eq!(cluster.characters.len(), 1);
+        assert!(ClusterParser::read_borrowed(&data[..100]).is_err());
+    }
+
+    #[test]
+    fn corrupt_uploads() {
+        let rex = rex();
+        let player = player();
+        let creatures = [("Broken", &rex[..40]), ("Rexy - Lvl 150", &rex[..])];
+        let data = cluster(&creatures, &player[..40]);
+        let cluster = ClusterParser::read_borrowed(&data).unwrap();
+
+        // The other uploads still come through
+        assert_eq!(cluster.items.len(), 1);
+        assert_eq!(cluster.creatures.len(), 1);
+        let creature = &cluster.creatures[0];
+        assert_eq!(creature.name.as_deref(), Some("Rexy - Lvl 150"));
+        let status = creature.entry.status_component().unwrap();
+        let level = status
+            .properties
+            .get_i32(&id(&cluster.names, "BaseCharacterLevel"));
+        assert_eq!(level, Some(150));
+        assert_eq!(cluster.characters.len(), 1);
+        assert_eq!(cluster.characters[0].name.as_deref(), Some("Bob"));
+        assert!(cluster.characters[0].profile.is_none());
+
+        let paths: Vec<_> = cluster.upload_errors.iter().map(|e| e.path()).collect();
+        assert_eq!(paths.len(), 2);
+        assert_eq!(paths[0][0], "PlayerDataBytes");
+        assert_eq!(paths[1][0], "DinoData");
+    }
+}
@@ -0,0 +1,311 @@
+use super::CryopodParser;
+use crate::io::{ArrayReader, Owned};
+use crate::object::{Names, ObjectRecord};
+use crate::properties::{Properties, Value};
+use crate::ArkError;
+use regex::Regex;
+
+/// An item class that stores creatures as a cryopod style payload, and the
+/// property path from the item to the byte array holding it.
+pub struct CreatureContainer {
+    pub class: Regex,
+    pub path: Vec<String>,
+}
+
+impl CreatureContainer {
+    pub fn new(class_pattern: &str, path: &[&str]) -> std::result::Result<Self, regex::Error> {
+        Ok(CreatureContainer {
+            class: Regex::new(class_pattern)?,
+            path: path.iter().map(|name| String::from(*name)).collect(),
+        })
+    }
+
+    /// The vanilla cryopod.
+    pub fn cryopod() -> Self {
+        CreatureContainer::new(
+            "^PrimalItem_WeaponEmptyCryopod_C$",
+            &["CustomItemDatas", "CustomDataBytes", "ByteArrays", "Bytes"],
+        )
+        .unwrap()
+    }
+}
+
+/// The items searched for stored creatures, such as cryopods, soul traps
+/// and vivariums. Defaults to just the vanilla cryopod; register others to
+/// extract creatures from modded containers.
+pub struct CreatureContainers {
+    containers: Vec<CreatureContainer>,
+}
+
+impl Default for CreatureContainers {
+    fn default() -> Self {
+        CreatureContainers {
+            containers: vec![CreatureContainer::cryopod()],
+        }
+    }
+}
+
+impl CreatureContainers {
+    /// A registry without any containers, which extracts nothing.
+    pub fn empty() -> Self {
+        CreatureContainers {
+            containers: Vec::new(),
+        }
+    }
+
+    pub fn register(&mut self, container: CreatureContainer) -> &mut Self {
+        self.containers.push(container);
+        self
+    }
+
+    /// Reads the creatures stored in containers, linking each one back to
+    /// its container. Component indexes inside a payload are relative to it.
+    /// Payloads that can't be read are skipped, returning their errors.
+    pub(crate) fn extract<'a>(
+        &self,
+        objects: &[ObjectRecord<'a>],
+        names: &mut Names<'a>,
+    ) -> (Vec<ObjectRecord<'a>>, Vec<ArkError>) {
+        let mut frozen = Vec::new();
+        let mut errors = Vec::new();
+        for container in &self.containers {
+            let path: Option<Vec<usize>> = container
+                .path
+                .iter()
+                .map(|name| names.get_name_id(name).copied())
+                .collect();
+            let path = match path {
+                Some(path) if !path.is_empty() => path,
+                _ => continue,
+            };
+            for (index, object) in objects.iter().enumerate() {
+                if !container.class.is_match(&names[object.name.id]) {
+                    continue;
+                }
+                let data = match find_bytes(&object.properties, &path) {
+                    Some(data) if !data.is_empty() => data,
+                    _ => continue,
+                };
+                let first_index = objects.len() + frozen.len();
+                let mut frozen_data = ArrayReader::from(data);
+                let parser = match CryopodParser::read(&mut Owned(&mut frozen_data), names) {
+                    Ok(parser) => parser,
+                    Err(e) => {
+                        errors.push(e.in_object(index, &names[object.name.id]));
+                        continue;
+                    }
+                };
+                for mut record in parser.objects {
+                    record.first_index = first_index;
+                    record.cryopod = Some(index);
+                    frozen.push(record);
+                }
+            }
+        }
+        (frozen, errors)
+    }
+}
+
+/// Follows `path` through structs and the first element of struct arrays,
+/// such as `CustomItemDatas`, to the byte array at its end.
+fn find_bytes<'p>(properties: &'p Properties, path: &[usize]) -> Option<&'p Vec<u8>> {
+    let (last, path) = path.split_last()?;
+    let mut properties = properties;
+    for id in path {
+        properties = *properties.get_structs(id).first()?;
+    }
+    match properties.get(last)? {
+        Value::ArrayOfU8(data) => Some(data),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::{name, names, property};
+    use crate::io::{Name, Writer};
+    use std::io::Cursor;
+
+    /// A cryopod payload holding one creature, with names saved as strings.
+    fn payload() -> Vec<u8> {
+        let mut data = Vec::new();
+        data.write_i32(1).unwrap();
+        data.write_u128(0x99).unwrap();
+        data.write_str("Dodo_Character_BP_C_4").unwrap();
+        data.write_bool(false).unwrap();
+        data.write_i32(0).unwrap();
+        data.write_i32(0).unwrap();
+        data.write_i32(0).unwrap();
+        data.write_bool(false).unwrap();
+        data.write_i32(data.len() as i32 + 8).unwrap();
+        data.write_i32(0).unwrap();
+        data.write_str("TargetingTeam").unwrap();
+        data.write_str("IntProperty").unwrap();
+        data.write_u32(4).unwrap();
+        data.write_u32(0).unwrap();
+        data.write_i32(5).unwrap();
+        data.write_str("None").unwrap();
+        data
+    }
+
+    /// The properties of a cryopod, with `bytes` stored like the game does.
+    fn cryopod(bytes: &[u8]) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.write_i32(bytes.len() as i32).unwrap();
+        data.write_bytes(bytes).unwrap();
+        let mut tag = Vec::new();
+        tag.write_name(&name("ByteProperty", 0)).unwrap();
+        let mut byte_arrays = Vec::new();
+        byte_arrays.write_i32(1).unwrap();
+        property(&mut byte_arrays, "Bytes", "ArrayProperty", &tag, &data);
+        byte_arrays.write_name(&name("None", 0)).unwrap();
+
+        let mut tag = Vec::new();
+        tag.write_name(&name("StructProperty", 0)).unwrap();
+        let mut custom_bytes = Vec::new();
+        property(
+            &mut custom_bytes,
+            "ByteArrays",
+            "ArrayProperty",
+            &tag,
+            &byte_arrays,
+        );
+        custom_bytes.write_name(&name("None", 0)).unwrap();
+
+        let mut struct_tag = Vec::new();
+        struct_tag
+            .write_name(&name("CustomItemByteArrays", 0))
+            .unwrap();
+        let mut datas = Vec::new();
+        datas.write_i32(1).unwrap();
+        property(
+            &mut datas,
+            "CustomDataBytes",
+            "StructProperty",
+            &struct_tag,
+            &custom_bytes,
+        );
+        datas.write_name(&name("None", 0)).unwrap();
+
+        let mut file = Vec::new();
+        property(&mut file, "CustomItemDatas", "ArrayProperty", &tag, &datas);
+        file.write_name(&name("None", 0)).unwrap();
+        file
+    }
+
+    fn record<'a>(class: Name, properties: Properties<'a>) -> ObjectRecord<'a> {
+        ObjectRecord {
+            guid: 0,
+            name: class,
+            is_item: true,
+            extra_names: Vec::new(),
+            from_data_file: 0,
+            data_file_index: 0,
+            location: None,
+            properties,
+            unknown: 0,
+            hibernation: None,
+            cryopod: None,
+            first_index: 0,
+        }
+    }
+
+    #[test]
+    fn cryopods() {
+        let mut names = names();
+        let mut objects = Vec::new();
+        for class in &["Dodo_Character_BP_C", "PrimalItem_WeaponEmptyCryopod_C"] {
+            let mut properties = Properties::new();
+            let data = cryopod(&payload());
+            properties
+                .read(&mut Owned(&mut Cursor::new(data)), &mut names)
+                .unwrap();
+            objects.push(record(name(class, 0), properties));
+        }
+
+        let (frozen, errors) = CreatureContainers::default().extract(&objects, &mut names);
+        assert!(errors.is_empty());
+        assert_eq!(frozen.len(), 1);
+        let dodo = &frozen[0];
+        assert_eq!(dodo.guid, 0x99);
+        assert_eq!(names.full_name(&dodo.name), "Dodo_Character_BP_C_4");
+        assert_eq!(dodo.cryopod, Some(1));
+        assert_eq!(dodo.first_index, 2);
+        let team = names.get_name_id("TargetingTeam").unwrap();
+        assert_eq!(dodo.properties.get_i32(team), Some(5));
+
+        let (frozen, _) = CreatureContainers::empty().extract(&objects, &mut names);
+        assert!(frozen.is_empty());
+    }
+
+    #[test]
+    fn unreadable_payloads() {
+        let mut names = names();
+        let mut objects = Vec::new();
+        let mut corrupt = payload();
+        corrupt.truncate(40);
+        for data in &[corrupt, payload()] {
+            let mut properties = Properties::new();
+            properties
+                .read(&mut Owned(&mut Cursor::new(cryopod(data))), &mut names)
+                .unwrap();
+            let class = name("PrimalItem_WeaponEmptyCryopod_C", 0);
+            objects.push(record(class, properties));
+        }
+
+        // The other cryopod is still read
+        let (frozen, errors) = CreatureContainers::default().extract(&objects, &mut names);
+        assert_eq!(frozen.len(), 1);
+        assert_eq!(frozen[0].cryopod, Some(1));
+        assert_eq!(errors.len(), 1);
+        let object = errors[0].object().unwrap();
+        assert_eq!(object.index, 0);
+        assert_eq!(object.class, "PrimalItem_WeaponEmptyCryopod_C");
+    }
+
+    #[test]
+    fn registry() {
+        let mut names = names();
+        let soul_trap = Name {
+            id: names.intern("PrimalItem_Soultrap_DS_C".into()),
+            instance: 0,
+        };
+        let mut objects = Vec::new();
+        for class in &[name("PrimalItem_WeaponEmptyCryopod_C", 0), soul_trap] {
+            let mut properties = Properties::new();
+            properties
+                .read(
+                    &mut Owned(&mut Cursor::new(cryopod(&payload()))),
+                    &mut names,
+                )
+                .unwrap();
+            objects.push(record(*class, properties));
+        }
+        let path = ["CustomItemDatas", "CustomDataBytes", "ByteArrays", "Bytes"];
+        let soul_traps = || CreatureContainer::new("^PrimalItem_Soultrap_", &path).unwrap();
+
+        // Only registered classes are searched
+        let mut containers = CreatureContainers::empty();
+        containers.register(soul_traps());
+        let (frozen, _) = containers.extract(&objects, &mut names);
+        assert_eq!(frozen.len(), 1);
+        assert_eq!(frozen[0].cryopod, Some(1));
+
+        let mut containers = CreatureContainers::default();
+        containers.register(soul_traps());
+        let (frozen, _) = containers.extract(&objects, &mut names);
+        let mut found: Vec<_> = frozen.iter().map(|r| r.cryopod).collect();
+        found.sort();
+        assert_eq!(found, vec![Some(0), Some(1)]);
+
+        // A path through properties the save doesn't have finds nothing
+        let mut containers = CreatureContainers::empty();
+        let path = ["CustomItemDatas", "SoulData", "Bytes"];
+        containers.register(CreatureContainer::new("^PrimalItem_Soultrap_", &path).unwrap());
+        let (frozen, errors) = containers.extract(&objects, &mut names);
+        assert!(frozen.is_empty() && errors.is_empty());
+
+        assert!(CreatureContainer::new("(", &path).is_err());
+    }
+}
@@ -1,53 +1,143 @@
-use crate::object::{Name, Names};
+use crate::object::{NameFormat, Names, ObjectRecord};
 use crate::properties::Properties;
-use crate::Object;
-use crate::{io::Reader, Location};
-use std::io::{Result, SeekFrom};
-use std::rc::Rc;
+use crate::{io::Source, Location};
+use crate::{ArkError, Result};
+use std::convert::TryFrom;
+use std::io::SeekFrom;
 
-pub struct CryopodParser {
-    pub objects: Vec<Object>,
+pub struct CryopodParser<'a> {
+    pub(crate) objects: Vec<ObjectRecord<'a>>,
 }
 
-impl CryopodParser {
-    pub fn read(file: &mut dyn Reader, names: &Rc<Names>) -> Result<Self> {
-        let count = file.read_i32()?;
-        let mut objects = Vec::with_capacity(count as usize);
-        for _ in 0..count {
-            let guid = file.read_u128()?;
-            let _name = file.read_str()?;
-            let is_item = file.read_bool()?;
-
-            let extra_class_count = file.read_i32()?;
-            for _ in 0..extra_class_count {
-                file.read_str()?;
+impl<'a> CryopodParser<'a> {
+    /// Reads the objects in a cryopod payload. Payloads have no names table,
+    /// so their names are strings which are added to `names`.
+    pub fn read(file: &mut dyn Source<'a>, names: &mut Names<'a>) -> Result<Self> {
+        let format = std::mem::replace(&mut names.format, NameFormat::Strings);
+        let objects = read_objects(file, names);
+        names.format = format;
+        Ok(CryopodParser { objects: objects? })
+    }
+}
+
+fn read_objects<'a>(
+    file: &mut dyn Source<'a>,
+    names: &mut Names<'a>,
+) -> Result<Vec<ObjectRecord<'a>>> {
+    let count = file.read_i32()?;
+    let mut objects = Vec::with_capacity((count.max(0) as usize).min(4096));
+    for index in 0..count {
+        let guid = file.read_u128()?;
+        let name = names.read_name(file)?;
+        let is_item = file.read_bool()?;
+
+        let extra_class_count = file.read_i32()?;
+        let mut extra_names = Vec::with_capacity((extra_class_count.max(0) as usize).min(4096));
+        for _ in 0..extra_class_count {
+            extra_names.push(names.read_name(file)?);
+        }
+        let from_data_file = file.read_i32()?;
+        let data_file_index = file.read_i32()?;
+
+        let location = if file.read_bool()? {
+            Some(Location::read(file)?)
+        } else {
+            None
+        };
+
+        let object_properties_offset = file.read_i32()?;
+        let mut properties = Properties::new();
+        let unknown = file.read_i32()?;
+        let next_object = file.stream_position()?;
+
+        let target = u64::try_from(object_properties_offset).map_err(|_| {
+            ArkError::InvalidData {
+                offset: next_object - 8,
+                message: format!("Invalid properties offset {}", object_properties_offset),
             }
-            // println!("{:?}", location);
-            file.seek(SeekFrom::Current(8))?;
-
-            let location = if file.read_bool()? {
-                Some(Location::read(file)?)
-            } else {
-                None
-            };
-
-            let object_properties_offset = file.read_i32()? as u64;
-            let mut properties = Properties::new();
-            let next_object = file.seek(SeekFrom::Current(4))?;
-
-            file.seek(SeekFrom::Start(object_properties_offset))?;
-            properties.read(file, names)?;
-            file.seek(SeekFrom::Start(next_object))?;
-
-            objects.push(Object::new(
-                guid,
-                Name { id: 0, instance: 0 },
-                is_item,
-                location,
-                properties,
-                names.clone(),
-            ));
+            .in_object(index as usize, &names[name.id])
+        })?;
+        file.seek(SeekFrom::Start(target))?;
+        properties
+            .read(file, names)
+            .map_err(|e| e.in_object(index as usize, &names[name.id]))?;
+        file.seek(SeekFrom::Start(next_object))?;
+
+        objects.push(ObjectRecord {
+            guid,
+            name,
+            is_item,
+            extra_names,
+            from_data_file,
+            data_file_index,
+            location,
+            properties,
+            unknown,
+            hibernation: None,
+            cryopod: None,
+            first_index: 0,
+        });
+    }
+    Ok(objects)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::{name, names};
+    use crate::io::{ArrayReader, Writer};
+    use crate::properties::Value;
+
+    fn object(data: &mut Vec<u8>, class: &str) {
+        data.write_u128(0x99).unwrap();
+        data.write_str(class).unwrap();
+        data.write_bool(false).unwrap();
+        data.write_i32(0).unwrap();
+        data.write_i32(0).unwrap();
+        data.write_i32(0).unwrap();
+        data.write_bool(false).unwrap();
+        data.write_i32(data.len() as i32 + 8).unwrap();
+        data.write_i32(0).unwrap();
+    }
+
+    #[test]
+    fn string_names() {
+        let mut data = Vec::new();
+        data.write_i32(1).unwrap();
+        object(&mut data, "Dodo_Character_BP_C_4");
+        data.write_str("StatusType").unwrap();
+        data.write_str("ByteProperty").unwrap();
+        data.write_u32(13).unwrap();
+        data.write_u32(0).unwrap();
+        data.write_str("EStatus").unwrap();
+        data.write_str("Torpor_2").unwrap();
+        data.write_str("None").unwrap();
+
+        let mut names = names();
+        let parser = CryopodParser::read(&mut ArrayReader::new(&data), &mut names).unwrap();
+        // Names are read as strings, but the table's own format is kept
+        assert!(matches!(names.format, NameFormat::Indexed));
+        let object = &parser.objects[0];
+        // Names already in the table keep their id
+        assert_eq!(object.name, name("Dodo_Character_BP_C", 5));
+        let status = *names.get_name_id("StatusType").unwrap();
+        match object.properties.get(&status) {
+            Some(Value::Enum(enum_type, value)) => {
+                assert_eq!(&names[enum_type.id], "EStatus");
+                assert_eq!((&names[value.id], value.instance), ("Torpor", 3));
+            }
+            value => panic!("Expected an enum, found {:?}", value),
         }
-        Ok(CryopodParser { objects })
+    }
+
+    #[test]
+    fn unreadable_payloads() {
+        // The table's format is also kept when a payload can't be read
+        let mut names = names();
+        let mut data = Vec::new();
+        data.write_i32(1).unwrap();
+        object(&mut data, "Dodo_Character_BP_C_4");
+        assert!(CryopodParser::read(&mut ArrayReader::new(&data), &mut names).is_err());
+        assert!(matches!(names.format, NameFormat::Indexed));
     }
 }
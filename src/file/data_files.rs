@@ -0,0 +1,98 @@
+use crate::io::{Reader, Writer};
+use crate::Result;
+use serde::Serialize;
+
+/// The objects saved into one of the streamed sub-levels in `data_files`.
+#[derive(Debug, Serialize)]
+pub struct DataFileObjects {
+    /// Index into `ArkParser::data_files`.
+    pub level: i32,
+    /// Object names, including their instance suffix.
+    pub objects: Vec<String>,
+}
+
+pub(crate) fn read_data_files(file: &mut dyn Reader) -> Result<Vec<String>> {
+    let count = file.read_i32()?;
+    let mut names = Vec::with_capacity((count.max(0) as usize).min(4096));
+    for _ in 0..count {
+        names.push(file.read_str()?);
+    }
+    Ok(names)
+}
+
+pub(crate) fn read_data_files_object_map(file: &mut dyn Reader) -> Result<Vec<DataFileObjects>> {
+    let count = file.read_i32()?;
+    let mut levels = Vec::with_capacity((count.max(0) as usize).min(4096));
+    for _ in 0..count {
+        let level = file.read_i32()?;
+        let object_count = file.read_u32()?;
+        let mut objects = Vec::with_capacity(object_count.min(4096) as usize);
+        for _ in 0..object_count {
+            objects.push(file.read_str()?);
+        }
+        levels.push(DataFileObjects { level, objects });
+    }
+    Ok(levels)
+}
+
+pub(crate) fn write_data_files(file: &mut dyn Writer, names: &[String]) -> Result<()> {
+    file.write_i32(names.len() as i32)?;
+    for name in names {
+        file.write_str(name)?;
+    }
+    Ok(())
+}
+
+pub(crate) fn write_data_files_object_map(
+    file: &mut dyn Writer,
+    levels: &[DataFileObjects],
+) -> Result<()> {
+    file.write_i32(levels.len() as i32)?;
+    for level in levels {
+        file.write_i32(level.level)?;
+        file.write_u32(level.objects.len() as u32)?;
+        for object in &level.objects {
+            file.write_str(object)?;
+        }
+    }
+    Ok(())
+}
+
+/// The persistent level is the first data file without its own objects,
+/// named without the `_P` or `_WP` suffix some maps use for it.
+pub(crate) fn map_name(data_files: &[String], object_map: &[DataFileObjects]) -> String {
+    let persistent = data_files
+        .iter()
+        .enumerate()
+        .find(|(i, _)| !object_map.iter().any(|l| l.level == *i as i32))
+        .or_else(|| data_files.iter().enumerate().next())
+        .map(|(_, name)| name.as_str())
+        .unwrap_or_default();
+    let name = persistent
+        .strip_suffix("_WP")
+        .or_else(|| persistent.strip_suffix("_P"))
+        .unwrap_or(persistent);
+    String::from(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(strings: &[&str]) -> Vec<String> {
+        strings.iter().map(|s| String::from(*s)).collect()
+    }
+
+    #[test]
+    fn map_names() {
+        let levels = vec![DataFileObjects {
+            level: 0,
+            objects: strings(&["Rock_12"]),
+        }];
+        let data_files = strings(&["Ragnarok_Ocean", "Ragnarok_P"]);
+        assert_eq!(map_name(&data_files, &levels), "Ragnarok");
+        let data_files = strings(&["TheIsland"]);
+        assert_eq!(map_name(&data_files, &[]), "TheIsland");
+        assert_eq!(map_name(&[], &[]), "");
+    }
+}
@@ -0,0 +1,236 @@
+//! Editing the objects of a parsed map save before writing it back out with
+//! `ArkWriter`.
+//!
+//! Objects refer to each other by their index, so deleting or adding objects
+//! renumbers every object reference and component index to match.
+
+use super::ArkParser;
+use crate::object::{Entry, Object};
+use crate::properties::Value;
+use crate::{ArkError, Result};
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::rc::Rc;
+
+const INVENTORY_ITEMS: &[&str] = &["InventoryItems", "EquippedItems"];
+const DINO_IDS: &[&str] = &["DinoID1", "DinoID2"];
+
+impl<'a> ArkParser<'a> {
+    /// Every object, in the order of their indexes.
+    pub fn objects(&self) -> &[Object<'a>] {
+        &self.objects
+    }
+
+    /// Changes the object at `index`, such as setting its `location` to move
+    /// it or calling `Object::set_property`.
+    ///
+    /// Only objects from the object list are written back, so objects read
+    /// from hibernation or creature containers can't be edited.
+    pub fn edit_object<R>(
+        &mut self,
+        index: usize,
+        edit: impl FnOnce(&mut Object<'a>) -> R,
+    ) -> Result<R> {
+        self.check_listed(index)?;
+        let result = edit(&mut self.objects_mut()?[index]);
+        self.update_entries();
+        Ok(result)
+    }
+
+    /// Deletes objects along with their status and inventory components and
+    /// the items in their inventories. References to deleted objects are
+    /// cleared.
+    pub fn delete_objects(&mut self, indexes: &[usize]) -> Result<()> {
+        let mut deleted = vec![false; self.objects.len()];
+        for index in indexes {
+            for owned in self.owned_objects(*index)? {
+                deleted[owned] = true;
+            }
+        }
+
+        let mut next = 0;
+        let new_indexes: Vec<Option<usize>> = deleted
+            .iter()
+            .map(|deleted| {
+                if *deleted {
+                    None
+                } else {
+                    next += 1;
+                    Some(next - 1)
+                }
+            })
+            .collect();
+        self.remap(&|i| new_indexes.get(i).copied().unwrap_or(Some(i)))?;
+
+        let mut index = 0;
+        self.objects_mut()?.retain(|_| {
+            index += 1;
+            !deleted[index - 1]
+        });
+        self.object_count -= deleted.iter().filter(|d| **d).count();
+        self.update_entries();
+        Ok(())
+    }
+
+    /// Copies an object along with its status and inventory components, as
+    /// new instances at the end of the object list. Items aren't copied, so
+    /// the copy's inventory is empty. Copies get a new guid, and a copied
+    /// creature new `DinoID1` and `DinoID2` values. Returns the index of the
+    /// copy.
+    pub fn clone_object(&mut self, index: usize) -> Result<usize> {
+        self.check_listed(index)?;
+        let count = self.object_count;
+        let original = &self.objects[index];
+        let inventory = original.inventory_component.filter(|i| *i < count);
+        let mut group = vec![index];
+        group.extend(original.status_component.filter(|i| *i < count));
+        group.extend(inventory);
+
+        // Make room for the copies before anything that isn't in the list
+        let added = group.len();
+        self.remap(&|i| Some(if i >= count { i + added } else { i }))?;
+
+        let mut copies: Vec<Object<'a>> = Vec::with_capacity(added);
+        for i in &group {
+            let mut copy = self.objects[*i].clone();
+            copy.name.instance = self
+                .objects
+                .iter()
+                .chain(&copies)
+                .filter(|o| o.name.id == copy.name.id)
+                .map(|o| o.name.instance)
+                .max()
+                .unwrap_or(0)
+                + 1;
+            copies.push(copy);
+        }
+
+        let renamed: Vec<_> = group
+            .iter()
+            .zip(&copies)
+            .map(|(i, copy)| (self.objects[*i].name, copy.name))
+            .collect();
+        let copy_index = |i: usize| group.iter().position(|g| *g == i).map(|p| count + p);
+        let names = self.names.clone();
+        for (i, copy) in group.iter().zip(&mut copies) {
+            copy.properties.remap_objects(&names, &|r| {
+                if r < 0 {
+                    return r;
+                }
+                copy_index(r as usize).map_or(r, |r| r as i32)
+            });
+            copy.status_component = copy.status_component.map(|c| copy_index(c).unwrap_or(c));
+            copy.inventory_component = copy.inventory_component.map(|c| copy_index(c).unwrap_or(c));
+            // Components name the object that owns them
+            for name in &mut copy.extra_names {
+                if let Some((_, new)) = renamed.iter().find(|(old, _)| old == name) {
+                    *name = *new;
+                }
+            }
+            // The game tells objects and creatures apart by these ids
+            if copy.guid != 0 {
+                copy.guid = (random() as u128) << 64 | random() as u128;
+            }
+            for dino_id in DINO_IDS {
+                let value = names
+                    .get_name_id(dino_id)
+                    .and_then(|id| copy.properties.get(id));
+                let value = match value {
+                    Some(Value::Int(_)) => Value::Int(random() as i32),
+                    Some(Value::UInt32(_)) => Value::UInt32(random() as u32),
+                    _ => continue,
+                };
+                copy.set_property(dino_id, value)?;
+            }
+            if Some(*i) == inventory {
+                for items in INVENTORY_ITEMS {
+                    if copy.properties.contains(items) {
+                        copy.set_property(items, Value::ArrayOfObject(Vec::new()))?;
+                    }
+                }
+            }
+        }
+
+        self.objects_mut()?.splice(count..count, copies);
+        self.object_count += added;
+        self.update_entries();
+        Ok(count)
+    }
+
+    fn check_listed(&self, index: usize) -> Result<()> {
+        if index >= self.object_count {
+            return Err(ArkError::UnknownObject { index });
+        }
+        Ok(())
+    }
+
+    /// The object with its components and inventory items.
+    fn owned_objects(&self, index: usize) -> Result<Vec<usize>> {
+        self.check_listed(index)?;
+        let listed = |i: &usize| *i < self.object_count;
+        let object = &self.objects[index];
+        let mut owned = vec![index];
+        owned.extend(object.status_component.filter(listed));
+        if let Some(inventory) = object.inventory_component.filter(listed) {
+            owned.push(inventory);
+            let inventory = &self.objects[inventory];
+            for items in INVENTORY_ITEMS {
+                let id = match self.names.get_name_id(items) {
+                    Some(id) => id,
+                    None => continue,
+                };
+                if let Some(Value::ArrayOfObject(items)) = inventory.properties.get(id) {
+                    owned.extend(items.iter().filter_map(|item| match item {
+                        Value::Int(i) if *i >= 0 && listed(&(*i as usize)) => Some(*i as usize),
+                        _ => None,
+                    }));
+                }
+            }
+        }
+        Ok(owned)
+    }
+
+    /// Renumbers object references in the object list and the component
+    /// indexes of every object, clearing references to removed objects.
+    fn remap(&mut self, map: &dyn Fn(usize) -> Option<usize>) -> Result<()> {
+        let names = self.names.clone();
+        let count = self.object_count;
+        let references = |i: i32| {
+            if i < 0 {
+                return i;
+            }
+            map(i as usize).map_or(-1, |i| i as i32)
+        };
+        for (index, object) in self.objects_mut()?.iter_mut().enumerate() {
+            // References in other sections are relative to the section
+            if index < count {
+                object.properties.remap_objects(&names, &references);
+            }
+            object.status_component = object.status_component.and_then(map);
+            object.inventory_component = object.inventory_component.and_then(map);
+            object.cryopod = object.cryopod.and_then(map);
+        }
+        Ok(())
+    }
+
+    fn objects_mut(&mut self) -> Result<&mut Vec<Object<'a>>> {
+        // Entries share the objects, so are rebuilt after editing
+        self.entries.clear();
+        if Rc::get_mut(&mut self.objects).is_none() {
+            self.update_entries();
+            return Err(ArkError::ObjectsInUse);
+        }
+        Ok(Rc::get_mut(&mut self.objects).unwrap())
+    }
+
+    fn update_entries(&mut self) {
+        self.entries = (0..self.objects.len())
+            .map(|i| Entry::new(&self.objects, i))
+            .collect();
+    }
+}
+
+/// A random number, from the keys std picks for each `RandomState`.
+fn random() -> u64 {
+    RandomState::new().build_hasher().finish()
+}
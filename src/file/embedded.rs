@@ -0,0 +1,239 @@
+use crate::io::{Reader, Writer};
+use crate::{ArkError, Result};
+use serde::Serialize;
+use std::io::Cursor;
+
+/// Binary data stored alongside an actor, such as a painting canvas.
+///
+/// The data is split into parts, each made of blobs whose sizes are stored
+/// as a count of 32 bit words. Painting canvases are decoded by `painting`;
+/// `bytes` and `words` join the parts for callers reading other formats.
+#[derive(Debug, Serialize)]
+pub struct EmbeddedData {
+    pub path: String,
+    pub parts: Vec<Vec<Vec<u8>>>,
+}
+
+/// A painted canvas, stored in the same layout as a `.pnt` file.
+#[derive(Debug, Serialize)]
+pub struct Painting {
+    pub width: u32,
+    pub height: u32,
+    /// One dye index per pixel, row by row, with 0 for unpainted pixels.
+    pub pixels: Vec<u8>,
+}
+
+impl Painting {
+    /// The dye index of a pixel, or `None` outside the canvas.
+    pub fn pixel(&self, x: u32, y: u32) -> Option<u8> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        self.pixels
+            .get(y as usize * self.width as usize + x as usize)
+            .copied()
+    }
+}
+
+impl EmbeddedData {
+    pub fn read(file: &mut dyn Reader) -> Result<Self> {
+        let path = file.read_str()?;
+        let part_count = file.read_i32()?;
+        let mut parts = Vec::with_capacity((part_count.max(0) as usize).min(4096));
+        for _ in 0..part_count {
+            let blob_count = file.read_i32()?;
+            let mut blobs = Vec::with_capacity((blob_count.max(0) as usize).min(4096));
+            for _ in 0..blob_count {
+                let words = file.read_i32()?;
+                if words < 0 {
+                    return Err(ArkError::InvalidData {
+                        offset: file.stream_position()? - 4,
+                        message: format!("Embedded data blob with {} words", words),
+                    });
+                }
+                blobs.push(file.read_bytes(words as usize * 4)?);
+            }
+            parts.push(blobs);
+        }
+        Ok(EmbeddedData { path, parts })
+    }
+
+    /// Writes the data back, padding blobs to a whole number of words.
+    pub fn write(&self, file: &mut dyn Writer) -> Result<()> {
+        file.write_str(&self.path)?;
+        file.write_i32(self.parts.len() as i32)?;
+        for blobs in &self.parts {
+            file.write_i32(blobs.len() as i32)?;
+            for blob in blobs {
+                let words = blob.len().div_ceil(4);
+                file.write_i32(words as i32)?;
+                file.write_bytes(blob)?;
+                file.write_bytes(&[0; 3][..words * 4 - blob.len()])?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Every blob of every part joined in order.
+    pub fn bytes(&self) -> Vec<u8> {
+        self.parts.iter().flatten().flatten().copied().collect()
+    }
+
+    /// The data as the little endian words it is stored in.
+    pub fn words(&self) -> Vec<u32> {
+        self.bytes()
+            .chunks_exact(4)
+            .map(|w| u32::from_le_bytes([w[0], w[1], w[2], w[3]]))
+            .collect()
+    }
+
+    /// Decodes the data as a painting canvas: a version, the width and
+    /// height, an unused value and the pixel count, followed by a byte per
+    /// pixel.
+    pub fn painting(&self) -> Result<Painting> {
+        let bytes = self.bytes();
+        let mut file = Cursor::new(&bytes);
+        let version = file.read_i32()?;
+        if version != 1 {
+            return Err(ArkError::UnsupportedVersion {
+                offset: 0,
+                version: version as i16,
+            });
+        }
+        let width = file.read_u32()?;
+        let height = file.read_u32()?;
+        file.read_i32()?;
+        let count = file.read_u32()?;
+        if Some(count as u64) != (width as u64).checked_mul(height as u64) {
+            return Err(ArkError::InvalidData {
+                offset: 16,
+                message: format!("Painting of {}x{} with {} pixels", width, height, count),
+            });
+        }
+        // Blobs are padded to whole words, so there may be bytes left over
+        let pixels = file.read_bytes(count as usize)?;
+        Ok(Painting {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    /// The object name at the end of `path`.
+    pub fn name(&self) -> &str {
+        match self.path.rfind(['.', '/', ':']) {
+            Some(i) => &self.path[i + 1..],
+            None => &self.path,
+        }
+    }
+}
+
+pub(crate) fn write_embedded_data(file: &mut dyn Writer, data: &[EmbeddedData]) -> Result<()> {
+    file.write_i32(data.len() as i32)?;
+    for data in data {
+        data.write(file)?;
+    }
+    Ok(())
+}
+
+pub(crate) fn read_embedded_data(file: &mut dyn Reader) -> Result<Vec<EmbeddedData>> {
+    let data_count = file.read_i32()?;
+    let mut data = Vec::with_capacity((data_count.max(0) as usize).min(4096));
+    for _ in 0..data_count {
+        data.push(EmbeddedData::read(file)?);
+    }
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parts() {
+        let mut file = Vec::new();
+        file.write_i32(1).unwrap();
+        file.write_str("/Game/Maps/TheIsland.TheIsland:Canvas_3")
+            .unwrap();
+        file.write_i32(2).unwrap();
+        for blobs in &[&[&[1u8, 0, 0, 0][..], &[2, 0, 0, 0, 3, 0, 0, 0]][..], &[]] {
+            file.write_i32(blobs.len() as i32).unwrap();
+            for blob in *blobs {
+                file.write_i32(blob.len() as i32 / 4).unwrap();
+                file.write_bytes(blob).unwrap();
+            }
+        }
+
+        let data = read_embedded_data(&mut Cursor::new(&file)).unwrap();
+        assert_eq!(data.len(), 1);
+        assert_eq!(data[0].name(), "Canvas_3");
+        assert_eq!(data[0].parts.len(), 2);
+        assert_eq!(data[0].words(), vec![1, 2, 3]);
+        let mut written = Vec::new();
+        write_embedded_data(&mut written, &data).unwrap();
+        assert_eq!(written, file);
+
+        // Blobs are padded to whole words
+        let data = EmbeddedData {
+            path: String::from("Canvas"),
+            parts: vec![vec![vec![1, 2, 3, 4, 5]]],
+        };
+        let mut written = Vec::new();
+        data.write(&mut written).unwrap();
+        let data = EmbeddedData::read(&mut Cursor::new(&written)).unwrap();
+        assert_eq!(data.bytes(), vec![1, 2, 3, 4, 5, 0, 0, 0]);
+
+        let mut corrupt = written.clone();
+        let words = corrupt.len() - 12;
+        corrupt[words..words + 4].copy_from_slice(&(-1i32).to_le_bytes());
+        assert!(matches!(
+            EmbeddedData::read(&mut Cursor::new(&corrupt)),
+            Err(ArkError::InvalidData { .. })
+        ));
+    }
+
+    #[test]
+    fn paintings() {
+        let mut canvas = Vec::new();
+        for v in &[1, 3, 2, 0, 6] {
+            canvas.write_i32(*v).unwrap();
+        }
+        canvas.write_bytes(&[0, 1, 2, 3, 4, 5]).unwrap();
+        // Split across blobs like the game does
+        let data = EmbeddedData {
+            path: String::from("/Game/Maps/TheIsland.TheIsland:Canvas_3"),
+            parts: vec![vec![canvas[..12].to_vec(), canvas[12..].to_vec()]],
+        };
+        let mut written = Vec::new();
+        data.write(&mut written).unwrap();
+        let data = EmbeddedData::read(&mut Cursor::new(&written)).unwrap();
+        let painting = data.painting().unwrap();
+        assert_eq!((painting.width, painting.height), (3, 2));
+        assert_eq!(painting.pixels, vec![0, 1, 2, 3, 4, 5]);
+        assert_eq!(painting.pixel(2, 0), Some(2));
+        assert_eq!(painting.pixel(0, 1), Some(3));
+        assert_eq!(painting.pixel(3, 0), None);
+
+        let corrupt = |header: &[i32]| {
+            let mut canvas = canvas.clone();
+            for (i, v) in header.iter().enumerate() {
+                canvas[i * 4..i * 4 + 4].copy_from_slice(&v.to_le_bytes());
+            }
+            let data = EmbeddedData {
+                path: String::from("Canvas"),
+                parts: vec![vec![canvas]],
+            };
+            data.painting().unwrap_err()
+        };
+        assert!(matches!(corrupt(&[2]), ArkError::UnsupportedVersion { .. }));
+        assert!(matches!(
+            corrupt(&[1, 3, 2, 0, 7]),
+            ArkError::InvalidData { offset: 16, .. }
+        ));
+        // More pixels than the data holds
+        assert!(matches!(
+            corrupt(&[1, 3, 3, 0, 9]),
+            ArkError::UnexpectedEof { .. }
+        ));
+    }
+}
@@ -0,0 +1,89 @@
+use crate::io::{Reader, Writer};
+use crate::{ArkError, Result};
+use serde::Serialize;
+
+/// The fixed fields at the start of a map save.
+#[derive(Debug, Clone, Serialize)]
+pub struct SaveHeader {
+    pub version: i16,
+    /// Start of the hibernation section, only present from version 7.
+    pub hibernation_offset: Option<u64>,
+    pub names_offset: u64,
+    pub properties_offset: u64,
+    /// Seconds of in game time when the save was written. Timers such as
+    /// incubation and mating cooldowns are stored relative to this clock.
+    pub game_time: f32,
+    /// Number of times the map has been saved, only present from version 9.
+    pub save_count: Option<u32>,
+}
+
+impl SaveHeader {
+    pub fn read(file: &mut dyn Reader, file_size: u64) -> Result<Self> {
+        let version = file.read_i16()?;
+        if !(5..=9).contains(&version) {
+            return Err(ArkError::UnsupportedVersion { offset: 0, version });
+        }
+
+        let hibernation_offset = if version > 6 {
+            let hibernation_offset = file.read_u32()? as u64;
+            let zero_value = file.read_i32()?;
+            if zero_value != 0 {
+                return Err(ArkError::InvalidHeader {
+                    offset: file.stream_position()? - 4,
+                    message: String::from("Found non zero value in header"),
+                });
+            }
+            Some(hibernation_offset)
+        } else {
+            None
+        };
+
+        let names_offset = file.read_u32()? as u64;
+        check_offset(file, names_offset, file_size)?;
+        let properties_offset = file.read_i32()? as u64;
+        check_offset(file, properties_offset, file_size)?;
+        let game_time = file.read_f32()?;
+
+        let save_count = if version > 8 {
+            Some(file.read_u32()?)
+        } else {
+            None
+        };
+
+        Ok(SaveHeader {
+            version,
+            hibernation_offset,
+            names_offset,
+            properties_offset,
+            game_time,
+            save_count,
+        })
+    }
+}
+
+impl SaveHeader {
+    pub fn write(&self, file: &mut dyn Writer) -> Result<()> {
+        file.write_i16(self.version)?;
+        if self.version > 6 {
+            file.write_u32(self.hibernation_offset.unwrap_or(0) as u32)?;
+            file.write_i32(0)?;
+        }
+        file.write_u32(self.names_offset as u32)?;
+        file.write_i32(self.properties_offset as i32)?;
+        file.write_f32(self.game_time)?;
+        if self.version > 8 {
+            file.write_u32(self.save_count.unwrap_or(0))?;
+        }
+        Ok(())
+    }
+}
+
+fn check_offset(file: &mut dyn Reader, target: u64, file_size: u64) -> Result<()> {
+    if target >= file_size {
+        return Err(ArkError::InvalidOffset {
+            offset: file.stream_position()? - 4,
+            target,
+        });
+    }
+    Ok(())
+}
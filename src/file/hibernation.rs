@@ -0,0 +1,278 @@
+use super::ark::read_objects;
+use super::SaveHeader;
+use crate::io::{Reader, Source};
+use crate::object::{NameFormat, Names, ObjectRecord};
+use crate::{ArkError, Result};
+use serde::Serialize;
+use std::convert::TryFrom;
+use std::io::SeekFrom;
+use std::rc::Rc;
+
+/// A hibernation cell, holding objects the server has unloaded from an area
+/// of the map nobody is near, mostly wild creatures.
+#[derive(Debug, Serialize)]
+pub struct HibernationEntry {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub unknown_byte: u8,
+    pub unknown_float: f32,
+    pub zone_volumes: Vec<String>,
+    pub unknown_int: i32,
+    pub class_index: i32,
+    /// The class from the section's class list that `class_index` refers to.
+    pub class: Option<String>,
+}
+
+/// Reads every object stored in the hibernation section of version 7+ saves.
+///
+/// Each cell has its own names table, which is merged into `names` so the
+/// returned objects share ids with the rest of the save. `first_index` is
+/// the number of objects already read, as component indexes inside a cell
+/// are relative to that cell.
+pub(crate) fn read_hibernation<'a>(
+    file: &mut dyn Source<'a>,
+    header: &SaveHeader,
+    names: &mut Names<'a>,
+    first_index: usize,
+) -> Result<Vec<ObjectRecord<'a>>> {
+    let offset = match header.hibernation_offset {
+        Some(offset) if offset > 0 => offset,
+        _ => return Ok(Vec::new()),
+    };
+    file.seek(SeekFrom::Start(offset))?;
+    if header.version > 7 {
+        file.seek(SeekFrom::Current(16))?;
+    }
+    if file.stream_position()? == header.names_offset {
+        return Ok(Vec::new());
+    }
+
+    file.read_i32()?;
+    file.read_i32()?;
+
+    let class_count = file.read_i32()?;
+    let mut classes = Vec::with_capacity((class_count.max(0) as usize).min(4096));
+    for _ in 0..class_count {
+        classes.push(file.read_str()?);
+    }
+
+    let index_count = file.read_i32()?;
+    if index_count != class_count {
+        return Err(ArkError::InvalidData {
+            offset: file.stream_position()? - 4,
+            message: format!(
+                "Hibernation has {} classes but {} indexes",
+                class_count, index_count
+            ),
+        });
+    }
+    for _ in 0..index_count {
+        file.read_i32()?;
+    }
+
+    let entry_count = file.read_i32()?;
+    let mut objects = Vec::new();
+    for _ in 0..entry_count {
+        let index = first_index + objects.len();
+        objects.append(&mut read_entry(file, names, &classes, index)?);
+    }
+    Ok(objects)
+}
+
+fn read_entry<'a>(
+    file: &mut dyn Source<'a>,
+    names: &mut Names<'a>,
+    classes: &[String],
+    first_index: usize,
+) -> Result<Vec<ObjectRecord<'a>>> {
+    let x = file.read_f32()?;
+    let y = file.read_f32()?;
+    let z = file.read_f32()?;
+    let unknown_byte = file.read_u8()?;
+    let unknown_float = file.read_f32()?;
+
+    let names_end = read_end(file)?;
+    let version = file.read_i32()?;
+    if version != 3 {
+        return Err(ArkError::UnsupportedVersion {
+            offset: file.stream_position()? - 4,
+            version: version as i16,
+        });
+    }
+    let name_count = file.read_i32()?;
+    let mut table = Vec::with_capacity((name_count.max(0) as usize).min(4096));
+    for _ in 0..name_count {
+        let name = file.read_string()?;
+        table.push(names.intern_with_instance(name));
+    }
+    let zone_count = file.read_i32()?;
+    let mut zone_volumes = Vec::with_capacity((zone_count.max(0) as usize).min(4096));
+    for _ in 0..zone_count {
+        zone_volumes.push(file.read_str()?);
+    }
+    file.seek(SeekFrom::Start(names_end))?;
+
+    let objects_end = read_end(file)?;
+    let objects_start = file.stream_position()?;
+    names.format = NameFormat::Remapped(table);
+    let objects = read_objects(file, names, objects_start, objects_end);
+    names.format = NameFormat::Indexed;
+    let mut objects = objects?.0;
+    file.seek(SeekFrom::Start(objects_end))?;
+
+    let unknown_int = file.read_i32()?;
+    let class_index = file.read_i32()?;
+    let entry = Rc::new(HibernationEntry {
+        x,
+        y,
+        z,
+        unknown_byte,
+        unknown_float,
+        zone_volumes,
+        unknown_int,
+        class_index,
+        class: classes.get(class_index as usize).cloned(),
+    });
+
+    for object in &mut objects {
+        object.hibernation = Some(entry.clone());
+        object.first_index = first_index;
+    }
+    Ok(objects)
+}
+
+/// Reads the size of the section that follows and returns where it ends.
+fn read_end(file: &mut dyn Reader) -> Result<u64> {
+    let size = file.read_i32()?;
+    let start = file.stream_position()?;
+    u64::try_from(size)
+        .ok()
+        .and_then(|size| start.checked_add(size))
+        .ok_or_else(|| ArkError::InvalidData {
+            offset: start - 4,
+            message: format!("Invalid section size {}", size),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::names;
+    use crate::io::{ArrayReader, Writer};
+    use std::io::Cursor;
+
+    /// A section with one cell holding a raptor, whose names are indexes
+    /// into the cell's own table.
+    fn section() -> Vec<u8> {
+        let mut file = vec![0; 4 + 16];
+        file.write_i32(0).unwrap();
+        file.write_i32(0).unwrap();
+        file.write_i32(1).unwrap();
+        file.write_str("Raptor_Character_BP_C").unwrap();
+        file.write_i32(1).unwrap();
+        file.write_i32(0).unwrap();
+        file.write_i32(1).unwrap();
+
+        for v in &[10.0f32, 20.0, 30.0] {
+            file.write_f32(*v).unwrap();
+        }
+        file.write_u8(1).unwrap();
+        file.write_f32(0.5).unwrap();
+        let mut table = Vec::new();
+        table.write_i32(3).unwrap();
+        let names = [
+            "Raptor_Character_BP_C_5",
+            "TargetingTeam",
+            "IntProperty",
+            "None",
+        ];
+        table.write_i32(names.len() as i32).unwrap();
+        for name in &names {
+            table.write_str(name).unwrap();
+        }
+        table.write_i32(1).unwrap();
+        table.write_str("Zone").unwrap();
+        file.write_i32(table.len() as i32).unwrap();
+        file.write_bytes(&table).unwrap();
+
+        let mut objects = Vec::new();
+        objects.write_i32(1).unwrap();
+        objects.write_u128(0x55).unwrap();
+        objects.write_u32(0).unwrap();
+        for v in &[0, 0, 0, 0, 0] {
+            objects.write_i32(*v).unwrap();
+        }
+        objects.write_i32(objects.len() as i32 + 8).unwrap();
+        objects.write_i32(0).unwrap();
+        for v in &[1, 2, 4, 0] {
+            objects.write_u32(*v).unwrap();
+        }
+        objects.write_i32(3).unwrap();
+        objects.write_u32(3).unwrap();
+        file.write_i32(objects.len() as i32).unwrap();
+        file.write_bytes(&objects).unwrap();
+
+        file.write_i32(7).unwrap();
+        file.write_i32(0).unwrap();
+        file
+    }
+
+    fn read<'a>(data: &'a [u8], names: &mut Names<'a>) -> Result<Vec<ObjectRecord<'a>>> {
+        let header = SaveHeader {
+            version: 9,
+            hibernation_offset: Some(4),
+            names_offset: data.len() as u64,
+            properties_offset: data.len() as u64,
+            game_time: 0.0,
+            save_count: Some(1),
+        };
+        read_hibernation(&mut ArrayReader::new(data), &header, names, 5)
+    }
+
+    #[test]
+    fn cells() {
+        let data = section();
+        let mut names = names();
+        let objects = read(&data, &mut names).unwrap();
+        assert!(matches!(names.format, NameFormat::Indexed));
+        assert_eq!(objects.len(), 1);
+        let raptor = &objects[0];
+        assert_eq!(raptor.guid, 0x55);
+        assert_eq!(raptor.first_index, 5);
+        assert_eq!(names.full_name(&raptor.name), "Raptor_Character_BP_C_5");
+        let team = names.get_name_id("TargetingTeam").unwrap();
+        assert_eq!(raptor.properties.get_i32(team), Some(3));
+
+        let cell = raptor.hibernation.as_ref().unwrap();
+        assert_eq!((cell.x, cell.y, cell.z), (10.0, 20.0, 30.0));
+        assert_eq!(cell.zone_volumes, vec!["Zone"]);
+        assert_eq!(cell.unknown_int, 7);
+        assert_eq!(cell.class.as_deref(), Some("Raptor_Character_BP_C"));
+    }
+
+    #[test]
+    fn corrupt_counts() {
+        let data = section();
+        let position = |pattern: &[u8]| data.windows(pattern.len()).position(|w| w == pattern);
+        let classes = 28;
+        let names_table = position(b"Raptor_Character_BP_C_5").unwrap() - 8;
+        let zones = position(b"Zone").unwrap() - 8;
+        for at in &[classes, names_table, zones] {
+            let mut data = data.clone();
+            data[*at..*at + 4].copy_from_slice(&i32::MAX.to_le_bytes());
+            assert!(read(&data, &mut names()).is_err());
+        }
+    }
+
+    #[test]
+    fn section_sizes() {
+        let mut file = Cursor::new([0, 0, 0, 0, 16, 0, 0, 0, 255, 255, 255, 255]);
+        file.set_position(4);
+        assert_eq!(read_end(&mut file).unwrap(), 24);
+        assert!(matches!(
+            read_end(&mut file),
+            Err(ArkError::InvalidData { offset: 8, .. })
+        ));
+    }
+}
@@ -1,5 +1,27 @@
 mod ark;
+mod asa;
+mod cluster;
+mod containers;
 mod cryopod;
+mod data_files;
+mod edit;
+mod embedded;
+mod header;
+mod hibernation;
+mod profile;
+mod sqlite;
+mod tribe;
+mod writer;
 
 pub use ark::ArkParser;
+pub use asa::AsaParser;
+pub use cluster::{ClusterParser, UploadedCharacter, UploadedCreature, UploadedItem};
+pub use containers::{CreatureContainer, CreatureContainers};
 pub use cryopod::CryopodParser;
+pub use data_files::DataFileObjects;
+pub use embedded::{EmbeddedData, Painting};
+pub use header::SaveHeader;
+pub use hibernation::HibernationEntry;
+pub use profile::{PlayerProfile, ProfileParser};
+pub use tribe::{Tribe, TribeAlliance, TribeMember, TribeParser};
+pub use writer::ArkWriter;
@@ -0,0 +1,189 @@
+use super::CryopodParser;
+use crate::io::{ArrayReader, Owned, Reader, Source};
+use crate::object::{Names, Object, ObjectRecord};
+use crate::properties::{Properties, Value};
+use crate::{ArkError, Result};
+use serde::Serialize;
+use std::rc::Rc;
+
+/// The player details stored in a `.arkprofile` file.
+#[derive(Debug, Serialize)]
+pub struct PlayerProfile {
+    pub player_data_id: u64,
+    /// The platform id, such as a Steam id.
+    pub unique_id: Option<String>,
+    /// The platform account name.
+    pub player_name: Option<String>,
+    pub character_name: Option<String>,
+    pub tribe_id: Option<i32>,
+    pub level: i32,
+    pub experience: f32,
+    pub engram_points: i32,
+    /// Blueprint paths of the learned engrams.
+    pub engrams: Vec<String>,
+}
+
+pub struct ProfileParser<'a> {
+    pub version: i32,
+    pub names: Rc<Names<'a>>,
+    pub profile: PlayerProfile,
+    objects: Vec<Object<'a>>,
+}
+
+impl ProfileParser<'static> {
+    pub fn read(file: &mut dyn Reader) -> Result<Self> {
+        ProfileParser::parse(&mut Owned(file))
+    }
+}
+
+impl<'a> ProfileParser<'a> {
+    pub fn read_borrowed(data: &'a [u8]) -> Result<Self> {
+        ProfileParser::parse(&mut ArrayReader::new(data))
+    }
+
+    fn parse(file: &mut dyn Source<'a>) -> Result<Self> {
+        let version = file.read_i32()?;
+        let mut names = Names::with_strings();
+        let records = CryopodParser::read(file, &mut names)?.objects;
+        let profile =
+            PlayerProfile::find(&records, &names).ok_or_else(|| ArkError::InvalidData {
+                offset: 0,
+                message: String::from("Profile has no MyData"),
+            })?;
+        let names = Rc::new(names);
+        let objects: Vec<Object> = records
+            .into_iter()
+            .map(|record| record.into_object(&names))
+            .collect();
+
+        Ok(ProfileParser {
+            version,
+            names,
+            profile,
+            objects,
+        })
+    }
+
+    /// Every object in the profile, the player data first.
+    pub fn objects(&self) -> &[Object<'a>] {
+        &self.objects
+    }
+}
+
+impl PlayerProfile {
+    /// Reads the profile from the object holding `MyData`, as saved in
+    /// profiles and with characters uploaded to a cluster.
+    pub(crate) fn find(objects: &[ObjectRecord], names: &Names) -> Option<Self> {
+        let my_data = id(names, "MyData");
+        let data = objects
+            .iter()
+            .find_map(|o| o.properties.get_properties(&my_data))?;
+        Some(PlayerProfile::new(data, names))
+    }
+
+    fn new(data: &Properties, names: &Names) -> Self {
+        let empty = Properties::new();
+        let config = data
+            .get_properties(&id(names, "MyPlayerCharacterConfig"))
+            .unwrap_or(&empty);
+        let stats = data
+            .get_properties(&id(names, "MyPersistentCharacterStats"))
+            .unwrap_or(&empty);
+        let engrams = match stats.get(&id(names, "PlayerState_EngramBlueprints")) {
+            Some(Value::ArrayOfObject(items)) => items
+                .iter()
+                .filter_map(|item| match item {
+                    Value::Name(name) => Some(names.full_name(name).into_owned()),
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+
+        PlayerProfile {
+            player_data_id: data.get_u64(&id(names, "PlayerDataID")).unwrap_or(0),
+            unique_id: match data.get(&id(names, "UniqueID")) {
+                Some(Value::UniqueNetId { id, .. }) => Some(id.to_string()),
+                _ => None,
+            },
+            player_name: data.get_str(&id(names, "PlayerName")).map(String::from),
+            character_name: config
+                .get_str(&id(names, "PlayerCharacterName"))
+                .map(String::from),
+            tribe_id: data.get_i32(&id(names, "TribeID")),
+            level: 1 + stats
+                .get_i32(&id(names, "CharacterStatusComponent_ExtraCharacterLevel"))
+                .unwrap_or(0),
+            experience: stats
+                .get_f32(&id(names, "CharacterStatusComponent_ExperiencePoints"))
+                .unwrap_or(0.0),
+            engram_points: stats
+                .get_i32(&id(names, "PlayerState_TotalEngramPoints"))
+                .unwrap_or(0),
+            engrams,
+        }
+    }
+}
+
+/// Id of a property name, or the unused id 0 if the file doesn't contain it.
+pub(crate) fn id(names: &Names, name: &str) -> usize {
+    *names.get_name_id(name).unwrap_or(&0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::{player_data, str_objects};
+    use crate::io::Writer;
+
+    fn profile() -> Vec<u8> {
+        let mut file = Vec::new();
+        file.write_i32(1).unwrap();
+        let objects = str_objects(4, &[("PrimalPlayerDataBP_C", &player_data())]);
+        file.write_bytes(&objects).unwrap();
+        file
+    }
+
+    #[test]
+    fn profiles() {
+        let data = profile();
+        let parser = ProfileParser::read_borrowed(&data).unwrap();
+        assert_eq!(parser.version, 1);
+        let profile = &parser.profile;
+        assert_eq!(profile.player_data_id, 987654321);
+        assert_eq!(profile.unique_id.as_deref(), Some("76561198000000000"));
+        assert_eq!(profile.player_name.as_deref(), Some("bobby"));
+        assert_eq!(profile.character_name.as_deref(), Some("Bob"));
+        assert_eq!(profile.tribe_id, Some(1500000001));
+        assert_eq!(profile.level, 42);
+        assert_eq!(profile.experience, 12345.5);
+        assert_eq!(profile.engram_points, 300);
+        assert_eq!(
+            profile.engrams,
+            vec![
+                "/Game/Engram/EngramEntry_Campfire.EngramEntry_Campfire_C",
+                "/Game/Engram/EngramEntry_Bed_2",
+            ]
+        );
+        let objects = parser.objects();
+        assert_eq!(objects.len(), 1);
+        assert_eq!(&parser.names[objects[0].name.id], "PrimalPlayerDataBP_C");
+
+        let parser = ProfileParser::read(&mut std::io::Cursor::new(&data)).unwrap();
+        assert_eq!(parser.profile.player_data_id, 987654321);
+    }
+
+    #[test]
+    fn corrupt_profiles() {
+        let data = profile();
+        assert!(ProfileParser::read_borrowed(&data[..data.len() - 20]).is_err());
+
+        let at = data.windows(6).position(|w| w == b"MyData").unwrap();
+        let mut data = data;
+        data[at..at + 6].copy_from_slice(b"MyDato");
+        assert!(matches!(
+            ProfileParser::read_borrowed(&data),
+            Err(ArkError::InvalidData { .. })
+        ));
+    }
+}
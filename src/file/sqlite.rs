@@ -0,0 +1,341 @@
+//! Just enough of the SQLite file format to read the rows of a table.
+//!
+//! Only rowid tables in a database without a write ahead log are supported,
+//! which is how the game writes its saves. The tests read a database written
+//! by SQLite 3.40 through `tests/fixtures/generate.py`.
+
+use crate::{ArkError, Result};
+use std::collections::HashSet;
+
+const HEADER: &[u8] = b"SQLite format 3\0";
+
+#[derive(Debug)]
+pub(crate) enum Column {
+    Null,
+    Integer(i64),
+    #[allow(dead_code)]
+    Float(f64),
+    Blob(Vec<u8>),
+    Text(String),
+}
+
+pub(crate) struct Database<'d> {
+    data: &'d [u8],
+    page_size: usize,
+    usable_size: usize,
+}
+
+impl<'d> Database<'d> {
+    pub fn open(data: &'d [u8]) -> Result<Self> {
+        if data.len() < 100 || &data[..16] != HEADER {
+            return Err(ArkError::InvalidHeader {
+                offset: 0,
+                message: String::from("Not an SQLite database"),
+            });
+        }
+        let page_size = match u16::from_be_bytes([data[16], data[17]]) {
+            1 => 65536,
+            size => size as usize,
+        };
+        // The format requires at least 480 usable bytes, which keeps the
+        // payload size calculations from underflowing
+        let usable_size = page_size.checked_sub(data[20] as usize);
+        if !page_size.is_power_of_two() || page_size < 512 || usable_size < Some(480) {
+            return Err(ArkError::InvalidHeader {
+                offset: 16,
+                message: format!("Invalid SQLite page size {}", page_size),
+            });
+        }
+        let usable_size = usable_size.unwrap();
+        Ok(Database {
+            data,
+            page_size,
+            usable_size,
+        })
+    }
+
+    /// Every row of the named table, in rowid order.
+    pub fn table(&self, name: &str) -> Result<Vec<Vec<Column>>> {
+        for row in self.rows(1)? {
+            if let [Column::Text(kind), Column::Text(table), _, Column::Integer(root), ..] =
+                row.as_slice()
+            {
+                if kind == "table" && table == name {
+                    return self.rows(*root as usize);
+                }
+            }
+        }
+        Err(ArkError::InvalidData {
+            offset: 0,
+            message: format!("Database has no {} table", name),
+        })
+    }
+
+    fn rows(&self, root: usize) -> Result<Vec<Vec<Column>>> {
+        let mut rows = Vec::new();
+        let mut pages = vec![root];
+        let mut visited = HashSet::new();
+        while let Some(page) = pages.pop() {
+            // A corrupt tree could otherwise be followed forever
+            if !visited.insert(page) {
+                return Err(ArkError::InvalidData {
+                    offset: self.page_offset(page)? as u64,
+                    message: format!("SQLite page {} is in a table twice", page),
+                });
+            }
+            let start = self.page_offset(page)?;
+            let header = if page == 1 { start + 100 } else { start };
+            let kind = self.byte(header)?;
+            let cells = self.u16(header + 3)? as usize;
+            let (pointers, interior) = match kind {
+                0x0d => (header + 8, false),
+                0x05 => (header + 12, true),
+                _ => {
+                    return Err(ArkError::InvalidData {
+                        offset: header as u64,
+                        message: format!("Unexpected SQLite page type {}", kind),
+                    })
+                }
+            };
+            let mut children = Vec::new();
+            for cell in 0..cells {
+                let cell = start + self.u16(pointers + cell * 2)? as usize;
+                if interior {
+                    children.push(self.u32(cell)? as usize);
+                } else {
+                    rows.push(self.record(&self.payload(cell)?)?);
+                }
+            }
+            if interior {
+                children.push(self.u32(header + 8)? as usize);
+                // Popped from the end, so push in reverse to keep rowid order
+                pages.extend(children.into_iter().rev());
+            }
+        }
+        Ok(rows)
+    }
+
+    /// The payload of a table leaf cell, following any overflow pages.
+    fn payload(&self, cell: usize) -> Result<Vec<u8>> {
+        let (size, read) = self.varint(cell)?;
+        let (_, rowid) = self.varint(cell + read)?;
+        let start = cell + read + rowid;
+        let size = size as usize;
+        // Every byte is stored somewhere in the file, which also bounds how
+        // long a cycle of overflow pages is followed
+        if size > self.data.len() {
+            return Err(ArkError::InvalidData {
+                offset: cell as u64,
+                message: format!("SQLite payload size {}", size),
+            });
+        }
+
+        let max_local = self.usable_size - 35;
+        if size <= max_local {
+            return Ok(self.slice(start, size)?.to_vec());
+        }
+        let min_local = (self.usable_size - 12) * 32 / 255 - 23;
+        let local = min_local + (size - min_local) % (self.usable_size - 4);
+        let local = if local <= max_local { local } else { min_local };
+
+        let mut payload = Vec::with_capacity(size);
+        payload.extend_from_slice(self.slice(start, local)?);
+        let mut next = self.u32(start + local)? as usize;
+        while payload.len() < size {
+            if next == 0 {
+                return Err(ArkError::UnexpectedEof {
+                    offset: cell as u64,
+                    field: "SQLite overflow page",
+                });
+            }
+            let page = self.page_offset(next)?;
+            let bytes = (size - payload.len()).min(self.usable_size - 4);
+            payload.extend_from_slice(self.slice(page + 4, bytes)?);
+            next = self.u32(page)? as usize;
+        }
+        Ok(payload)
+    }
+
+    fn record(&self, payload: &[u8]) -> Result<Vec<Column>> {
+        let record = Database {
+            data: payload,
+            page_size: self.page_size,
+            usable_size: self.usable_size,
+        };
+        let (header_size, mut pos) = record.varint(0)?;
+        let mut types = Vec::new();
+        while pos < header_size as usize {
+            let (serial_type, read) = record.varint(pos)?;
+            types.push(serial_type);
+            pos += read;
+        }
+
+        let mut body = header_size as usize;
+        let mut columns = Vec::with_capacity(types.len());
+        for serial_type in types {
+            let (column, size) = match serial_type {
+                0 => (Column::Null, 0),
+                1..=6 => {
+                    let size = [1, 2, 3, 4, 6, 8][serial_type as usize - 1];
+                    (Column::Integer(record.int(body, size)?), size)
+                }
+                7 => {
+                    let bits = record.int(body, 8)? as u64;
+                    (Column::Float(f64::from_bits(bits)), 8)
+                }
+                8 => (Column::Integer(0), 0),
+                9 => (Column::Integer(1), 0),
+                n if n >= 12 && n % 2 == 0 => {
+                    let size = (n as usize - 12) / 2;
+                    (Column::Blob(record.slice(body, size)?.to_vec()), size)
+                }
+                n if n >= 13 => {
+                    let size = (n as usize - 13) / 2;
+                    let text = String::from_utf8_lossy(record.slice(body, size)?).into_owned();
+                    (Column::Text(text), size)
+                }
+                n => {
+                    return Err(ArkError::InvalidData {
+                        offset: body as u64,
+                        message: format!("Unknown SQLite serial type {}", n),
+                    })
+                }
+            };
+            columns.push(column);
+            body += size;
+        }
+        Ok(columns)
+    }
+
+    fn page_offset(&self, page: usize) -> Result<usize> {
+        match page
+            .checked_sub(1)
+            .and_then(|p| p.checked_mul(self.page_size))
+        {
+            Some(offset) if offset < self.data.len() => Ok(offset),
+            offset => Err(ArkError::InvalidOffset {
+                offset: 0,
+                target: offset.unwrap_or(0) as u64,
+            }),
+        }
+    }
+
+    fn slice(&self, offset: usize, len: usize) -> Result<&'d [u8]> {
+        match offset.checked_add(len) {
+            Some(end) if end <= self.data.len() => Ok(&self.data[offset..end]),
+            _ => Err(ArkError::UnexpectedEof {
+                offset: offset as u64,
+                field: "SQLite page",
+            }),
+        }
+    }
+
+    fn byte(&self, offset: usize) -> Result<u8> {
+        Ok(self.slice(offset, 1)?[0])
+    }
+
+    fn u16(&self, offset: usize) -> Result<u16> {
+        Ok(self.int(offset, 2)? as u16)
+    }
+
+    fn u32(&self, offset: usize) -> Result<u32> {
+        Ok(self.int(offset, 4)? as u32)
+    }
+
+    /// A big endian two's complement integer of `size` bytes.
+    fn int(&self, offset: usize, size: usize) -> Result<i64> {
+        let bytes = self.slice(offset, size)?;
+        let mut value = if bytes[0] & 0x80 != 0 { -1i64 } else { 0 };
+        for byte in bytes {
+            value = (value << 8) | *byte as i64;
+        }
+        Ok(value)
+    }
+
+    /// A variable length integer, returning it and the number of bytes read.
+    fn varint(&self, offset: usize) -> Result<(u64, usize)> {
+        let mut value = 0u64;
+        for i in 0..8 {
+            let byte = self.byte(offset + i)?;
+            value = (value << 7) | (byte & 0x7f) as u64;
+            if byte & 0x80 == 0 {
+                return Ok((value, i + 1));
+            }
+        }
+        // The ninth byte contributes all eight bits
+        Ok(((value << 8) | self.byte(offset + 8)? as u64, 9))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Forty rows of `items (id, name, data, weight)` in 512 byte pages, with
+    /// the data of row 20 spilling onto overflow pages, as written by
+    /// `tests/fixtures/generate.py`.
+    const ITEMS: &[u8] = include_bytes!("../../tests/fixtures/items.sqlite");
+
+    #[test]
+    fn tables() {
+        let database = Database::open(ITEMS).unwrap();
+        let rows = database.table("items").unwrap();
+        assert_eq!(rows.len(), 40);
+        for (i, row) in (1..).zip(&rows) {
+            match row.as_slice() {
+                [Column::Null, Column::Text(name), Column::Blob(data), weight] => {
+                    assert_eq!(name, &format!("Item {}", i));
+                    if i == 20 {
+                        assert_eq!(data.len(), 2048);
+                        assert!(data.iter().zip(0..=255).all(|(a, b)| *a == b));
+                    } else {
+                        assert_eq!(data, &vec![i as u8; i]);
+                    }
+                    assert!(match weight {
+                        Column::Float(weight) => i % 2 == 0 && *weight == i as f64 + 0.25,
+                        Column::Null => i % 2 == 1,
+                        _ => false,
+                    });
+                }
+                row => panic!("Unexpected row {:?}", row),
+            }
+        }
+        assert!(matches!(
+            database.table("game"),
+            Err(ArkError::InvalidData { .. })
+        ));
+    }
+
+    #[test]
+    fn corrupt_databases() {
+        let mut data = ITEMS.to_vec();
+        data[20] = 64;
+        assert!(matches!(
+            Database::open(&data),
+            Err(ArkError::InvalidHeader { offset: 16, .. })
+        ));
+        data[20] = 0;
+        data[16..18].copy_from_slice(&[0x03, 0x00]);
+        assert!(Database::open(&data).is_err());
+        assert!(Database::open(&ITEMS[..99]).is_err());
+
+        // An interior page listing itself as a child
+        let mut data = ITEMS.to_vec();
+        let root = 512;
+        assert_eq!(data[root], 0x05);
+        data[root + 8..root + 12].copy_from_slice(&2u32.to_be_bytes());
+        let database = Database::open(&data).unwrap();
+        assert!(matches!(
+            database.table("items"),
+            Err(ArkError::InvalidData { .. })
+        ));
+
+        // A payload larger than the file
+        let mut data = ITEMS.to_vec();
+        let leaf = 2 * 512;
+        let cell = leaf + u16::from_be_bytes([data[leaf + 8], data[leaf + 9]]) as usize;
+        data[cell..cell + 4].copy_from_slice(&[0xff, 0xff, 0xff, 0x7f]);
+        let database = Database::open(&data).unwrap();
+        assert!(database.table("items").is_err());
+    }
+}
@@ -0,0 +1,286 @@
+use super::profile::id;
+use super::CryopodParser;
+use crate::io::{ArrayReader, Owned, Reader, Source};
+use crate::object::{Entry, Names, Object};
+use crate::properties::{Properties, Value};
+use crate::{ArkError, Result};
+use serde::Serialize;
+use std::rc::Rc;
+
+/// The tribe details stored in a `.arktribe` file.
+#[derive(Debug, Serialize)]
+pub struct Tribe {
+    /// The id used as `TargetingTeam` by everything the tribe owns.
+    pub id: i32,
+    pub name: String,
+    pub owner_id: Option<u32>,
+    pub members: Vec<TribeMember>,
+    pub admins: Vec<u32>,
+    pub ranks: Vec<String>,
+    pub alliances: Vec<TribeAlliance>,
+    pub log: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TribeMember {
+    /// The player data id, as in `PlayerProfile::player_data_id`.
+    pub id: u32,
+    pub name: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TribeAlliance {
+    pub id: u32,
+    pub name: String,
+    pub tribe_ids: Vec<u32>,
+    pub tribe_names: Vec<String>,
+}
+
+pub struct TribeParser<'a> {
+    pub version: i32,
+    pub names: Rc<Names<'a>>,
+    pub tribe: Tribe,
+    objects: Vec<Object<'a>>,
+}
+
+impl TribeParser<'static> {
+    pub fn read(file: &mut dyn Reader) -> Result<Self> {
+        TribeParser::parse(&mut Owned(file))
+    }
+}
+
+impl<'a> TribeParser<'a> {
+    pub fn read_borrowed(data: &'a [u8]) -> Result<Self> {
+        TribeParser::parse(&mut ArrayReader::new(data))
+    }
+
+    fn parse(file: &mut dyn Source<'a>) -> Result<Self> {
+        let version = file.read_i32()?;
+        let mut names = Names::with_strings();
+        let records = CryopodParser::read(file, &mut names)?.objects;
+        let names = Rc::new(names);
+        let objects: Vec<Object> = records
+            .into_iter()
+            .map(|record| record.into_object(&names))
+            .collect();
+
+        let tribe_data = id(&names, "TribeData");
+        let data = objects
+            .iter()
+            .find_map(|o| o.properties.get_properties(&tribe_data))
+            .ok_or_else(|| ArkError::InvalidData {
+                offset: 0,
+                message: String::from("Tribe has no TribeData"),
+            })?;
+        let tribe = Tribe::new(data, &names);
+
+        Ok(TribeParser {
+            version,
+            names,
+            tribe,
+            objects,
+        })
+    }
+
+    pub fn objects(&self) -> &[Object<'a>] {
+        &self.objects
+    }
+}
+
+impl Tribe {
+    fn new(data: &Properties, names: &Names) -> Self {
+        let member_names = strings(data, names, "MembersPlayerName");
+        let members = u32s(data, names, "MembersPlayerDataID")
+            .into_iter()
+            .enumerate()
+            .map(|(i, id)| TribeMember {
+                id,
+                name: member_names.get(i).cloned().unwrap_or_default(),
+            })
+            .collect();
+        let ranks = data
+            .get_structs(&id(names, "TribeRankGroups"))
+            .iter()
+            .map(|rank| string(rank, names, "RankGroupName"))
+            .collect();
+        let alliances = data
+            .get_structs(&id(names, "TribeAlliances"))
+            .iter()
+            .map(|alliance| TribeAlliance {
+                id: alliance.get_u64(&id(names, "AllianceID")).unwrap_or(0) as u32,
+                name: string(alliance, names, "AllianceName"),
+                tribe_ids: u32s(alliance, names, "MembersTribeID"),
+                tribe_names: strings(alliance, names, "MembersTribeName"),
+            })
+            .collect();
+
+        Tribe {
+            id: data.get_i32(&id(names, "TribeID")).unwrap_or(0),
+            name: string(data, names, "TribeName"),
+            owner_id: data
+                .get_u64(&id(names, "OwnerPlayerDataID"))
+                .map(|id| id as u32),
+            members,
+            admins: u32s(data, names, "TribeAdmins"),
+            ranks,
+            alliances,
+            log: strings(data, names, "TribeLog"),
+        }
+    }
+
+    /// The tribe owning a structure or tame, found by its `TargetingTeam`.
+    pub fn of<'t>(tribes: &'t [Tribe], entry: &Entry) -> Option<&'t Tribe> {
+        let team = entry.targeting_team()?;
+        tribes.iter().find(|tribe| tribe.id == team)
+    }
+}
+
+fn string(data: &Properties, names: &Names, name: &str) -> String {
+    String::from(data.get_str(&id(names, name)).unwrap_or_default())
+}
+
+fn strings(data: &Properties, names: &Names, name: &str) -> Vec<String> {
+    match data.get(&id(names, name)) {
+        Some(Value::ArrayOfStr(items)) => items.iter().map(|s| String::from(&**s)).collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn u32s(data: &Properties, names: &Names, name: &str) -> Vec<u32> {
+    match data.get(&id(names, name)) {
+        Some(Value::ArrayOfU32(items)) => items.clone(),
+        Some(Value::ArrayOfI32(items)) => items.iter().map(|i| *i as u32).collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::{save, str_name, str_objects, str_property};
+    use crate::io::Writer;
+    use crate::ArkParser;
+
+    fn str_array(file: &mut Vec<u8>, prop: &str, items: &[&str]) {
+        let mut data = Vec::new();
+        data.write_i32(items.len() as i32).unwrap();
+        for item in items {
+            data.write_str(item).unwrap();
+        }
+        let tag = str_name("StrProperty");
+        str_property(file, prop, "ArrayProperty", &tag, &data);
+    }
+
+    fn u32_array(file: &mut Vec<u8>, prop: &str, items: &[u32]) {
+        let mut data = Vec::new();
+        data.write_i32(items.len() as i32).unwrap();
+        for item in items {
+            data.write_u32(*item).unwrap();
+        }
+        let tag = str_name("UInt32Property");
+        str_property(file, prop, "ArrayProperty", &tag, &data);
+    }
+
+    /// A tribe of two players with one rank, alliance and log entry.
+    fn tribe() -> Vec<u8> {
+        let mut rank = Vec::new();
+        rank.write_i32(1).unwrap();
+        let name = str_name("Officers");
+        str_property(&mut rank, "RankGroupName", "StrProperty", &[], &name);
+        rank.write_str("None").unwrap();
+
+        let mut alliance = Vec::new();
+        alliance.write_i32(1).unwrap();
+        let name = str_name("Pact");
+        str_property(&mut alliance, "AllianceName", "StrProperty", &[], &name);
+        let id = 77u32.to_le_bytes();
+        str_property(&mut alliance, "AllianceID", "UInt32Property", &[], &id);
+        str_array(&mut alliance, "MembersTribeName", &["A", "B"]);
+        u32_array(&mut alliance, "MembersTribeID", &[1, 2]);
+        alliance.write_str("None").unwrap();
+
+        let mut data = Vec::new();
+        let name = str_name("Bobs");
+        str_property(&mut data, "TribeName", "StrProperty", &[], &name);
+        let owner = 5u32.to_le_bytes();
+        str_property(
+            &mut data,
+            "OwnerPlayerDataID",
+            "UInt32Property",
+            &[],
+            &owner,
+        );
+        let id = 1500000001i32.to_le_bytes();
+        str_property(&mut data, "TribeID", "IntProperty", &[], &id);
+        str_array(&mut data, "MembersPlayerName", &["bob", "amy"]);
+        u32_array(&mut data, "MembersPlayerDataID", &[5, 6]);
+        u32_array(&mut data, "TribeAdmins", &[5]);
+        let tag = str_name("StructProperty");
+        str_property(&mut data, "TribeRankGroups", "ArrayProperty", &tag, &rank);
+        str_property(
+            &mut data,
+            "TribeAlliances",
+            "ArrayProperty",
+            &tag,
+            &alliance,
+        );
+        str_array(&mut data, "TribeLog", &["Day 1: hi"]);
+        data.write_str("None").unwrap();
+
+        let mut properties = Vec::new();
+        let tag = str_name("TribeData");
+        str_property(&mut properties, "TribeData", "StructProperty", &tag, &data);
+        properties.write_str("None").unwrap();
+
+        let mut file = Vec::new();
+        file.write_i32(1).unwrap();
+        let objects = str_objects(4, &[("PrimalTribeData", &properties)]);
+        file.write_bytes(&objects).unwrap();
+        file
+    }
+
+    #[test]
+    fn tribes() {
+        let data = tribe();
+        let parser = TribeParser::read_borrowed(&data).unwrap();
+        assert_eq!(parser.version, 1);
+        let tribe = &parser.tribe;
+        assert_eq!((tribe.id, tribe.name.as_str()), (1500000001, "Bobs"));
+        assert_eq!(tribe.owner_id, Some(5));
+        let members: Vec<_> = tribe.members.iter().map(|m| (m.id, &*m.name)).collect();
+        assert_eq!(members, vec![(5, "bob"), (6, "amy")]);
+        assert_eq!(tribe.admins, vec![5]);
+        assert_eq!(tribe.ranks, vec!["Officers"]);
+        let alliance = &tribe.alliances[0];
+        assert_eq!((alliance.id, alliance.name.as_str()), (77, "Pact"));
+        assert_eq!(alliance.tribe_ids, vec![1, 2]);
+        assert_eq!(alliance.tribe_names, vec!["A", "B"]);
+        assert_eq!(tribe.log, vec!["Day 1: hi"]);
+        assert_eq!(parser.objects().len(), 1);
+
+        let parser = TribeParser::read(&mut std::io::Cursor::new(&data)).unwrap();
+        assert_eq!(parser.tribe.members.len(), 2);
+        assert!(TribeParser::read_borrowed(&data[..data.len() - 20]).is_err());
+    }
+
+    #[test]
+    fn targeting_teams() {
+        let tribe = |id| Tribe {
+            id,
+            name: id.to_string(),
+            owner_id: None,
+            members: Vec::new(),
+            admins: Vec::new(),
+            ranks: Vec::new(),
+            alliances: Vec::new(),
+            log: Vec::new(),
+        };
+        let tribes = vec![tribe(1500000001), tribe(7)];
+        let data = save();
+        let parser = ArkParser::read_borrowed(&data).unwrap();
+        let entry = &parser.entries()[0];
+        assert_eq!(entry.targeting_team(), Some(7));
+        assert_eq!(Tribe::of(&tribes, entry).unwrap().name, "7");
+        assert!(Tribe::of(&tribes[..1], entry).is_none());
+    }
+}
@@ -0,0 +1,282 @@
+use super::data_files::{write_data_files, write_data_files_object_map};
+use super::embedded::write_embedded_data;
+use super::ArkParser;
+use crate::io::Writer;
+use crate::object::Object;
+use crate::properties::NameWriter;
+use crate::Result;
+use std::io::{Cursor, Seek, SeekFrom};
+
+/// Writes a parsed map save back out.
+///
+/// Objects from the object list are written with their properties, while
+/// the hibernation section is written as it was read, so changes to
+/// hibernated objects and creatures in cryopods aren't saved. A save that
+/// hasn't been changed is written byte for byte as it was read.
+pub struct ArkWriter<'p, 'a> {
+    parser: &'p ArkParser<'a>,
+}
+
+impl<'p, 'a> ArkWriter<'p, 'a> {
+    pub fn new(parser: &'p ArkParser<'a>) -> Self {
+        ArkWriter { parser }
+    }
+
+    pub fn write(&self, file: &mut dyn Writer) -> Result<()> {
+        file.write_bytes(&self.to_bytes()?)
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let parser = self.parser;
+        let names = &parser.names;
+        let sections = &parser.sections;
+        let objects = &parser.objects[..parser.object_count];
+        let mut name_writer = NameWriter::new(names);
+
+        // Property blocks come last, but the object list needs their offsets
+        let mut properties = Vec::new();
+        let mut offsets = Vec::with_capacity(objects.len());
+        for (index, object) in objects.iter().enumerate() {
+            offsets.push(properties.len() as i32);
+            object
+                .properties
+                .write(&mut properties, &mut name_writer)
+                .map_err(|e| e.in_object(index, &names[object.name.id]))?;
+        }
+
+        // The header is written again once the offsets are known
+        let mut header = parser.header.clone();
+        let mut file = Cursor::new(Vec::new());
+        header.write(&mut file)?;
+        write_data_files(&mut file, &parser.data_files)?;
+        write_embedded_data(&mut file, &parser.embedded_data)?;
+        write_data_files_object_map(&mut file, &parser.data_files_object_map)?;
+        file.write_i32(objects.len() as i32)?;
+        for (object, offset) in objects.iter().zip(offsets) {
+            write_object(&mut file, object, offset, &mut name_writer)?;
+        }
+
+        if let Some(offset) = sections.hibernation {
+            header.hibernation_offset = Some(file.position() + offset);
+        }
+        file.write_bytes(&sections.after_objects)?;
+
+        header.names_offset = file.position();
+        let name_count = names.table_len.max(name_writer.max_id);
+        file.write_i32(name_count as i32)?;
+        for id in 1..=name_count {
+            file.write_str(&names[id])?;
+        }
+        file.write_bytes(&sections.after_names)?;
+
+        header.properties_offset = file.position();
+        file.write_bytes(&properties)?;
+        file.write_bytes(&sections.trailer)?;
+
+        file.seek(SeekFrom::Start(0))?;
+        header.write(&mut file)?;
+        Ok(file.into_inner())
+    }
+}
+
+fn write_object(
+    file: &mut dyn Writer,
+    object: &Object,
+    properties_offset: i32,
+    names: &mut NameWriter,
+) -> Result<()> {
+    file.write_u128(object.guid)?;
+    names.write(file, &object.name)?;
+    file.write_bool(object.is_item)?;
+    file.write_i32(object.extra_names.len() as i32)?;
+    for name in &object.extra_names {
+        names.write(file, name)?;
+    }
+    file.write_i32(object.from_data_file)?;
+    file.write_i32(object.data_file_index)?;
+    match &object.location {
+        Some(location) => {
+            file.write_bool(true)?;
+            location.write(file)?;
+        }
+        None => file.write_bool(false)?,
+    }
+    file.write_i32(properties_offset)?;
+    file.write_i32(object.unknown)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::save;
+    use crate::object::Entry;
+    use crate::properties::Value;
+    use crate::ArkError;
+    use std::io::Cursor;
+
+    #[test]
+    fn round_trip() {
+        let data = save();
+        let parser = ArkParser::read_borrowed(&data).unwrap();
+        assert_eq!(parser.entries().len(), 2);
+        assert_eq!(ArkWriter::new(&parser).to_bytes().unwrap(), data);
+
+        let parser = ArkParser::read(&mut Cursor::new(&data)).unwrap();
+        let mut written = Vec::new();
+        ArkWriter::new(&parser).write(&mut written).unwrap();
+        assert_eq!(written, data);
+    }
+
+    fn id(parser: &ArkParser, name: &str) -> usize {
+        *parser.get_name_id(name).unwrap()
+    }
+
+    #[test]
+    fn edits_are_written() {
+        let data = save();
+        let mut parser = ArkParser::read_borrowed(&data).unwrap();
+        let owner = id(&parser, "Owner");
+        let team = id(&parser, "TargetingTeam");
+        parser
+            .edit_object(0, |object| {
+                // Values nested in structs are written like any other
+                match object.properties.get_mut(&owner) {
+                    Some(Value::Properties(inner)) => inner.set(&team, Value::Int(3))?,
+                    _ => panic!("Owner isn't a struct"),
+                }
+                object.set_property("TamedName", Value::String("Rex".into()))?;
+                object.set_property_index("TargetingTeam", 2, Value::Int(9))?;
+                object.remove_property("bIsFemale");
+                object.location.as_mut().unwrap().z = 100.0;
+                object.insert_property("Stats", "ArrayProperty", Some("FloatProperty"), {
+                    Value::ArrayOfF32(vec![1.0, 2.0])
+                })
+            })
+            .unwrap()
+            .unwrap();
+        // Objects that aren't written back can't be edited
+        assert!(matches!(
+            parser.edit_object(2, |_| ()),
+            Err(ArkError::UnknownObject { index: 2 })
+        ));
+        let mismatch = parser.edit_object(1, |object| {
+            object.set_property("TamedName", Value::Int(1)).unwrap_err()
+        });
+        assert!(matches!(
+            mismatch.unwrap().root(),
+            ArkError::TypeMismatch { .. }
+        ));
+
+        let written = ArkWriter::new(&parser).to_bytes().unwrap();
+        let parser = ArkParser::read_borrowed(&written).unwrap();
+        let dodo = parser.entries()[0].properties();
+        assert_eq!(dodo.get_str(&id(&parser, "TamedName")), Some("Rex"));
+        assert_eq!(
+            dodo.get_vec_int(&id(&parser, "TargetingTeam")),
+            vec![7, 0, 9]
+        );
+        assert!(!dodo.contains("bIsFemale"));
+        let inner = dodo.get_properties(&owner).unwrap();
+        assert_eq!(inner.get_i32(&team), Some(3));
+        assert!(matches!(
+            dodo.get(&id(&parser, "Stats")),
+            Some(Value::ArrayOfF32(stats)) if stats == &[1.0, 2.0]
+        ));
+        assert_eq!(parser.entries()[0].location().unwrap().z, 100.0);
+        let other = parser.entries()[1].properties();
+        assert_eq!(other.get_str(&id(&parser, "TamedName")), Some("Dodö"));
+    }
+
+    #[test]
+    fn object_headers() {
+        let data = save();
+        let mut parser = ArkParser::read_borrowed(&data).unwrap();
+        let object = &parser.objects()[0];
+        let extra = object.extra_names.iter();
+        let extra: Vec<_> = extra.map(|n| (parser.get_name(n.id), n.instance)).collect();
+        assert_eq!(extra, vec![("Dodo_Character_BP_C", 3)]);
+        assert_eq!((object.from_data_file, object.data_file_index), (1, 1));
+        assert_eq!(object.location.as_ref().unwrap().rotation.yaw, 90.0);
+        assert_eq!(object.unknown, 0);
+        assert!(parser.objects()[1].location.is_none());
+
+        parser
+            .edit_object(0, |object| {
+                object.extra_names[0].instance = 4;
+                object.data_file_index = 0;
+                object.location.as_mut().unwrap().rotation.roll = 45.0;
+                object.unknown = 7;
+            })
+            .unwrap();
+        let written = ArkWriter::new(&parser).to_bytes().unwrap();
+        let parser = ArkParser::read_borrowed(&written).unwrap();
+        let object = &parser.objects()[0];
+        assert_eq!(object.extra_names[0].instance, 4);
+        assert_eq!((object.from_data_file, object.data_file_index), (1, 0));
+        let rotation = object.location.as_ref().unwrap().rotation;
+        assert_eq!((rotation.yaw, rotation.roll), (90.0, 45.0));
+        assert_eq!(object.unknown, 7);
+    }
+
+    #[test]
+    fn delete_and_clone() {
+        let data = save();
+        let mut parser = ArkParser::read_borrowed(&data).unwrap();
+        parser
+            .edit_object(1, |object| {
+                object.insert_property("DinoID1", "IntProperty", None, Value::Int(5))?;
+                object.insert_property("DinoID2", "IntProperty", None, Value::Int(6))
+            })
+            .unwrap()
+            .unwrap();
+        assert_eq!(parser.clone_object(1).unwrap(), 2);
+        parser.delete_objects(&[0]).unwrap();
+
+        let written = ArkWriter::new(&parser).to_bytes().unwrap();
+        let parser = ArkParser::read_borrowed(&written).unwrap();
+        let target = id(&parser, "Target");
+        let entries = parser.entries();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].name().instance, 2);
+        assert_eq!(entries[1].name().instance, 3);
+        assert_eq!(entries[0].properties().get_i32(&target), Some(0));
+        // The copy refers to itself like the original did
+        assert_eq!(entries[1].properties().get_i32(&target), Some(1));
+        // but is a different object and creature
+        let objects = parser.objects();
+        assert_eq!(objects[0].guid, 0x1234);
+        assert_ne!(objects[1].guid, 0x1234);
+        let dino_ids = |i: usize| {
+            let properties = entries[i].properties();
+            let id1 = properties.get_i32(&id(&parser, "DinoID1"));
+            (id1, properties.get_i32(&id(&parser, "DinoID2")))
+        };
+        assert_eq!(dino_ids(0), (Some(5), Some(6)));
+        assert!(matches!(dino_ids(1), (Some(a), Some(b)) if (a, b) != (5, 6)));
+
+        // References to deleted objects are cleared
+        let mut parser = ArkParser::read_borrowed(&data).unwrap();
+        parser.delete_objects(&[1]).unwrap();
+        let properties = parser.entries()[0].properties();
+        assert_eq!(properties.get_i32(&target), Some(-1));
+        assert!(matches!(
+            properties.get(&id(&parser, "Levels")),
+            Some(Value::Map(entries))
+                if matches!(entries.as_slice(), [(Value::Int(5), Value::Int(-1))])
+        ));
+        assert!(matches!(
+            parser.delete_objects(&[1]),
+            Err(ArkError::UnknownObject { index: 1 })
+        ));
+
+        // Objects can't be changed under an entry that's still held
+        let kept = Entry::new(&parser.objects, 0);
+        assert!(matches!(
+            parser.clone_object(0),
+            Err(ArkError::ObjectsInUse)
+        ));
+        assert_eq!(parser.entries().len(), 1);
+        drop(kept);
+        assert_eq!(parser.clone_object(0).unwrap(), 1);
+    }
+}
@@ -0,0 +1,442 @@
+//! Saves and property lists built in memory for tests.
+
+use crate::io::{Name, Owned, Writer};
+use crate::object::Names;
+use std::io::Cursor;
+
+pub(crate) const NAMES: &[&str] = &[
+    "None",
+    "IntProperty",
+    "StrProperty",
+    "BoolProperty",
+    "ByteProperty",
+    "StructProperty",
+    "ArrayProperty",
+    "FloatProperty",
+    "Vector",
+    "Dodo_Character_BP_C",
+    "TargetingTeam",
+    "TamedName",
+    "bIsFemale",
+    "EDinoMood",
+    "DinoMood",
+    "Velocity",
+    "Stats",
+    "Owner",
+    "ObjectProperty",
+    "Target",
+    "SoftObjectProperty",
+    "Mod",
+    "Mods",
+    "MapProperty",
+    "SetProperty",
+    "NameProperty",
+    "Levels",
+    "Tags",
+    "Moods",
+    "Ancestors",
+    "Colors",
+    "TextProperty",
+    "SignText",
+    "Label",
+    "Rotator",
+    "Rotation",
+    "UniqueNetIdRepl",
+    "PlayerId",
+    "Box",
+    "Bounds",
+    "ItemNetID",
+    "ItemId",
+    "Note",
+    "PrimalItem_WeaponEmptyCryopod_C",
+    "CustomItemDatas",
+    "CustomDataBytes",
+    "CustomItemByteArrays",
+    "ByteArrays",
+    "Bytes",
+    "CustomColours_60_7D3267C846B277953C0C41AEBD54FBCB",
+    "Rotations",
+    "Locations",
+    "DinoID1",
+    "DinoID2",
+];
+
+pub(crate) fn name(name: &str, instance: u32) -> Name {
+    let id = NAMES.iter().position(|n| *n == name).unwrap() + 1;
+    Name { id, instance }
+}
+
+/// A names table holding `NAMES`, as read from a save.
+pub(crate) fn names() -> Names<'static> {
+    let mut table = Vec::new();
+    table.write_i32(NAMES.len() as i32).unwrap();
+    for name in NAMES {
+        table.write_str(name).unwrap();
+    }
+    Names::new(&mut Owned(&mut Cursor::new(table)), 0).unwrap()
+}
+
+pub(crate) fn property(file: &mut Vec<u8>, prop: &str, type_name: &str, tag: &[u8], data: &[u8]) {
+    file.write_name(&name(prop, 0)).unwrap();
+    file.write_name(&name(type_name, 0)).unwrap();
+    file.write_u32(data.len() as u32).unwrap();
+    file.write_u32(0).unwrap();
+    file.write_bytes(tag).unwrap();
+    file.write_bytes(data).unwrap();
+}
+
+pub(crate) fn properties() -> Vec<u8> {
+    let mut data = Vec::new();
+    let mut file = Vec::new();
+    property(
+        &mut file,
+        "TargetingTeam",
+        "IntProperty",
+        &[],
+        &[7, 0, 0, 0],
+    );
+    data.write_str("Dodö").unwrap();
+    property(&mut file, "TamedName", "StrProperty", &[], &data);
+    property(&mut file, "bIsFemale", "BoolProperty", &[1], &[]);
+
+    let mut tag = Vec::new();
+    tag.write_name(&name("EDinoMood", 0)).unwrap();
+    let mut data = Vec::new();
+    data.write_name(&name("EDinoMood", 3)).unwrap();
+    property(&mut file, "DinoMood", "ByteProperty", &tag, &data);
+
+    let mut tag = Vec::new();
+    tag.write_name(&name("Vector", 0)).unwrap();
+    let mut data = Vec::new();
+    for v in &[1.5f32, -2.0, 0.25] {
+        data.write_f32(*v).unwrap();
+    }
+    property(&mut file, "Velocity", "StructProperty", &tag, &data);
+
+    let mut tag = Vec::new();
+    tag.write_name(&name("IntProperty", 0)).unwrap();
+    let mut data = Vec::new();
+    data.write_i32(3).unwrap();
+    for v in &[4, 5, 6] {
+        data.write_i32(*v).unwrap();
+    }
+    property(&mut file, "Stats", "ArrayProperty", &tag, &data);
+
+    let mut tag = Vec::new();
+    tag.write_name(&name("Dodo_Character_BP_C", 0)).unwrap();
+    let mut data = Vec::new();
+    property(
+        &mut data,
+        "TargetingTeam",
+        "IntProperty",
+        &[],
+        &[1, 0, 0, 0],
+    );
+    data.write_name(&name("None", 0)).unwrap();
+    property(&mut file, "Owner", "StructProperty", &tag, &data);
+
+    let mut data = Vec::new();
+    data.write_i32(0).unwrap();
+    data.write_i32(1).unwrap();
+    property(&mut file, "Target", "ObjectProperty", &[], &data);
+    property(
+        &mut file,
+        "Mod",
+        "SoftObjectProperty",
+        &[],
+        &[1, 2, 3, 4, 5],
+    );
+
+    let mut tag = Vec::new();
+    tag.write_name(&name("SoftObjectProperty", 0)).unwrap();
+    property(
+        &mut file,
+        "Mods",
+        "ArrayProperty",
+        &tag,
+        &[1, 0, 0, 0, 9, 9],
+    );
+
+    let mut tag = Vec::new();
+    tag.write_name(&name("IntProperty", 0)).unwrap();
+    tag.write_name(&name("ObjectProperty", 0)).unwrap();
+    let mut data = Vec::new();
+    for v in &[0, 1, 5, 0, 1] {
+        data.write_i32(*v).unwrap();
+    }
+    property(&mut file, "Levels", "MapProperty", &tag, &data);
+
+    let mut tag = Vec::new();
+    tag.write_name(&name("NameProperty", 0)).unwrap();
+    let mut data = Vec::new();
+    data.write_i32(0).unwrap();
+    data.write_i32(2).unwrap();
+    data.write_name(&name("Mod", 0)).unwrap();
+    data.write_name(&name("Mods", 1)).unwrap();
+    property(&mut file, "Tags", "SetProperty", &tag, &data);
+
+    let mut tag = Vec::new();
+    tag.write_name(&name("ByteProperty", 0)).unwrap();
+    let mut data = Vec::new();
+    data.write_i32(2).unwrap();
+    data.write_name(&name("EDinoMood", 1)).unwrap();
+    data.write_name(&name("EDinoMood", 2)).unwrap();
+    property(&mut file, "Moods", "ArrayProperty", &tag, &data);
+
+    let mut tag = Vec::new();
+    tag.write_name(&name("StructProperty", 0)).unwrap();
+    let mut data = Vec::new();
+    data.write_i32(2).unwrap();
+    for team in &[[1, 0, 0, 0], [2, 0, 0, 0]] {
+        property(&mut data, "TargetingTeam", "IntProperty", &[], team);
+        data.write_name(&name("None", 0)).unwrap();
+    }
+    property(&mut file, "Ancestors", "ArrayProperty", &tag, &data);
+    property(
+        &mut file,
+        "Colors",
+        "ArrayProperty",
+        &tag,
+        &[2, 0, 0, 0, 0, 0, 255, 255, 255, 0, 0, 255],
+    );
+
+    let mut data = Vec::new();
+    data.write_u32(0).unwrap();
+    data.write_i8(0).unwrap();
+    for v in &["Signs", "A1B2", "Welcome"] {
+        data.write_str(v).unwrap();
+    }
+    property(&mut file, "SignText", "TextProperty", &[], &data);
+
+    let mut data = Vec::new();
+    data.write_u32(0).unwrap();
+    data.write_i8(1).unwrap();
+    data.write_u32(2).unwrap();
+    data.write_i8(0).unwrap();
+    for v in &["Labels", "C3D4", "{Count} dodos"] {
+        data.write_str(v).unwrap();
+    }
+    data.write_i32(1).unwrap();
+    data.write_str("Count").unwrap();
+    data.write_i8(0).unwrap();
+    data.write_i64(3).unwrap();
+    property(&mut file, "Label", "TextProperty", &[], &data);
+
+    let mut tag = Vec::new();
+    tag.write_name(&name("Rotator", 0)).unwrap();
+    let mut data = Vec::new();
+    for v in &[10.0f32, 90.0, 0.0] {
+        data.write_f32(*v).unwrap();
+    }
+    property(&mut file, "Rotation", "StructProperty", &tag, &data);
+
+    let mut tag = Vec::new();
+    tag.write_name(&name("UniqueNetIdRepl", 0)).unwrap();
+    let mut data = Vec::new();
+    data.write_i32(8).unwrap();
+    data.write_str("76561197960287930").unwrap();
+    property(&mut file, "PlayerId", "StructProperty", &tag, &data);
+
+    let mut tag = Vec::new();
+    tag.write_name(&name("Box", 0)).unwrap();
+    let mut data = Vec::new();
+    for v in &[-1.0f32, -2.0, -3.0, 1.0, 2.0, 3.0] {
+        data.write_f32(*v).unwrap();
+    }
+    data.write_u8(1).unwrap();
+    property(&mut file, "Bounds", "StructProperty", &tag, &data);
+
+    let mut tag = Vec::new();
+    tag.write_name(&name("ItemNetID", 0)).unwrap();
+    let mut data = Vec::new();
+    data.write_u32(12).unwrap();
+    data.write_u32(34).unwrap();
+    property(&mut file, "ItemId", "StructProperty", &tag, &data);
+
+    // An ASCII string saved as UTF-16, which can't be encoded the same way
+    let mut data = Vec::new();
+    data.write_i32(-3).unwrap();
+    data.write_bytes(&[b'H', 0, b'i', 0, 0, 0]).unwrap();
+    property(&mut file, "Note", "StrProperty", &[], &data);
+
+    file.write_name(&name("None", 0)).unwrap();
+    file
+}
+
+pub(crate) fn object(file: &mut Vec<u8>, location: bool, properties_offset: i32) {
+    file.write_u128(0x1234).unwrap();
+    file.write_name(&name("Dodo_Character_BP_C", 2)).unwrap();
+    file.write_bool(false).unwrap();
+    file.write_i32(1).unwrap();
+    file.write_name(&name("Dodo_Character_BP_C", 3)).unwrap();
+    file.write_i32(1).unwrap();
+    file.write_i32(1).unwrap();
+    file.write_bool(location).unwrap();
+    if location {
+        for v in &[1.0f32, 2.0, 3.0, 0.0, 90.0, 0.0] {
+            file.write_f32(*v).unwrap();
+        }
+    }
+    file.write_i32(properties_offset).unwrap();
+    file.write_i32(0).unwrap();
+}
+
+/// A version 9 save with two objects sharing the same properties.
+pub(crate) fn save() -> Vec<u8> {
+    let mut body = Vec::new();
+    body.write_i32(2).unwrap();
+    body.write_str("TheIsland").unwrap();
+    body.write_str("TheIsland_Caves").unwrap();
+    body.write_i32(1).unwrap();
+    body.write_str("/Game/Canvas.Canvas").unwrap();
+    body.write_i32(1).unwrap();
+    body.write_i32(1).unwrap();
+    body.write_i32(1).unwrap();
+    body.write_bytes(&[1, 2, 3, 4]).unwrap();
+    body.write_i32(1).unwrap();
+    body.write_i32(1).unwrap();
+    body.write_u32(1).unwrap();
+    body.write_str("Dodo_Character_BP_C_1").unwrap();
+
+    let properties = properties();
+    body.write_i32(2).unwrap();
+    object(&mut body, true, 0);
+    object(&mut body, false, properties.len() as i32);
+
+    let header_len = 26;
+    let hibernation_offset = header_len + body.len();
+    body.write_bytes(&[0; 16]).unwrap();
+    let names_offset = header_len + body.len();
+    body.write_i32(NAMES.len() as i32).unwrap();
+    for name in NAMES {
+        body.write_str(name).unwrap();
+    }
+    let properties_offset = header_len + body.len();
+    body.write_bytes(&properties).unwrap();
+    body.write_bytes(&properties).unwrap();
+
+    let mut file = Vec::new();
+    file.write_i16(9).unwrap();
+    file.write_u32(hibernation_offset as u32).unwrap();
+    file.write_i32(0).unwrap();
+    file.write_u32(names_offset as u32).unwrap();
+    file.write_i32(properties_offset as i32).unwrap();
+    file.write_f32(1234.5).unwrap();
+    file.write_u32(42).unwrap();
+    assert_eq!(file.len(), header_len);
+    file.write_bytes(&body).unwrap();
+    file
+}
+
+/// Like `property`, with names saved as strings as in cryopod payloads,
+/// profiles, tribes and cluster files.
+pub(crate) fn str_property(
+    file: &mut Vec<u8>,
+    prop: &str,
+    type_name: &str,
+    tag: &[u8],
+    data: &[u8],
+) {
+    file.write_str(prop).unwrap();
+    file.write_str(type_name).unwrap();
+    file.write_u32(data.len() as u32).unwrap();
+    file.write_u32(0).unwrap();
+    file.write_bytes(tag).unwrap();
+    file.write_bytes(data).unwrap();
+}
+
+/// A name saved as a string, such as the inner type in a property tag.
+pub(crate) fn str_name(name: &str) -> Vec<u8> {
+    let mut data = Vec::new();
+    data.write_str(name).unwrap();
+    data
+}
+
+/// Objects with names saved as strings, each given by its class and
+/// properties, for a file whose object list starts at `start`.
+pub(crate) fn str_objects(start: usize, objects: &[(&str, &[u8])]) -> Vec<u8> {
+    let headers: usize = objects
+        .iter()
+        .map(|(class, _)| 16 + str_name(class).len() + 28)
+        .sum();
+    let mut offset = start + 4 + headers;
+    let mut file = Vec::new();
+    file.write_i32(objects.len() as i32).unwrap();
+    for (i, (class, properties)) in objects.iter().enumerate() {
+        file.write_u128(i as u128 + 1).unwrap();
+        file.write_str(class).unwrap();
+        for v in &[0, 0, 0, 0, 0] {
+            file.write_i32(*v).unwrap();
+        }
+        file.write_i32(offset as i32).unwrap();
+        file.write_i32(0).unwrap();
+        offset += properties.len();
+    }
+    for (_, properties) in objects {
+        file.write_bytes(properties).unwrap();
+    }
+    file
+}
+
+/// The properties of a level 42 player in a tribe, with two engrams
+/// learned, as saved in profiles and uploaded characters.
+pub(crate) fn player_data() -> Vec<u8> {
+    let mut data = Vec::new();
+    data.write_i32(2).unwrap();
+    for engram in &[
+        "/Game/Engram/EngramEntry_Campfire.EngramEntry_Campfire_C",
+        "/Game/Engram/EngramEntry_Bed_2",
+    ] {
+        data.write_i32(1).unwrap();
+        data.write_str(engram).unwrap();
+    }
+    let mut stats = Vec::new();
+    let engrams = "PlayerState_EngramBlueprints";
+    let tag = str_name("ObjectProperty");
+    str_property(&mut stats, engrams, "ArrayProperty", &tag, &data);
+    let level = "CharacterStatusComponent_ExtraCharacterLevel";
+    str_property(&mut stats, level, "UInt16Property", &[], &[41, 0]);
+    let experience = "CharacterStatusComponent_ExperiencePoints";
+    let data = 12345.5f32.to_le_bytes();
+    str_property(&mut stats, experience, "FloatProperty", &[], &data);
+    let points = "PlayerState_TotalEngramPoints";
+    str_property(
+        &mut stats,
+        points,
+        "IntProperty",
+        &[],
+        &300i32.to_le_bytes(),
+    );
+    stats.write_str("None").unwrap();
+
+    let mut config = Vec::new();
+    let name = "PlayerCharacterName";
+    str_property(&mut config, name, "StrProperty", &[], &str_name("Bob"));
+    config.write_str("None").unwrap();
+
+    let mut my_data = Vec::new();
+    let id = 987654321u64.to_le_bytes();
+    str_property(&mut my_data, "PlayerDataID", "UInt64Property", &[], &id);
+    let mut data = Vec::new();
+    data.write_i32(0).unwrap();
+    data.write_str("76561198000000000").unwrap();
+    let tag = str_name("UniqueNetIdRepl");
+    str_property(&mut my_data, "UniqueID", "StructProperty", &tag, &data);
+    let name = str_name("bobby");
+    str_property(&mut my_data, "PlayerName", "StrProperty", &[], &name);
+    let tribe = 1500000001i32.to_le_bytes();
+    str_property(&mut my_data, "TribeID", "IntProperty", &[], &tribe);
+    let config_name = "MyPlayerCharacterConfig";
+    let tag = str_name("PrimalPlayerCharacterConfigStruct");
+    str_property(&mut my_data, config_name, "StructProperty", &tag, &config);
+    let stats_name = "MyPersistentCharacterStats";
+    let tag = str_name("PrimalPersistentCharacterStatsStruct");
+    str_property(&mut my_data, stats_name, "StructProperty", &tag, &stats);
+    my_data.write_str("None").unwrap();
+
+    let mut file = Vec::new();
+    let tag = str_name("PrimalPlayerDataStruct");
+    str_property(&mut file, "MyData", "StructProperty", &tag, &my_data);
+    file.write_str("None").unwrap();
+    file
+}
@@ -0,0 +1,225 @@
+//! Decoding and encoding of Unreal `FString`s.
+//!
+//! An FString is an `i32` length followed by the characters, including a
+//! trailing null. A positive length is a count of single byte characters, a
+//! negative length is a count of UTF-16 code units and zero is the empty
+//! string without any terminator.
+
+use std::borrow::Cow;
+
+/// Number of bytes following the length prefix.
+pub fn byte_len(size: i32) -> usize {
+    if size < 0 {
+        size.unsigned_abs() as usize * 2
+    } else {
+        size as usize
+    }
+}
+
+/// Decodes the characters of an FString whose length prefix was `size`.
+///
+/// `data` must be exactly `byte_len(size)` bytes. Borrowed single byte data
+/// is returned borrowed, owned data is reused without copying where possible.
+pub fn decode(size: i32, data: Cow<[u8]>) -> Result<Cow<str>, String> {
+    if data.len() != byte_len(size) {
+        return Err(format!(
+            "Expected {} bytes for string but found {}",
+            byte_len(size),
+            data.len()
+        ));
+    }
+    if size < 0 {
+        decode_utf16(&data).map(Cow::Owned)
+    } else {
+        Ok(decode_narrow(data))
+    }
+}
+
+/// The length prefix `encode` writes for a string.
+pub fn encoded_size(string: &str) -> i32 {
+    if string.is_empty() {
+        0
+    } else if string.is_ascii() {
+        string.len() as i32 + 1
+    } else {
+        -(string.encode_utf16().count() as i32 + 1)
+    }
+}
+
+/// Encodes a string as an FString length prefix and the bytes following it.
+///
+/// Like the game, strings are written as single bytes when every character
+/// is ASCII and as UTF-16 otherwise.
+pub fn encode(string: &str) -> (i32, Vec<u8>) {
+    if string.is_empty() {
+        (0, Vec::new())
+    } else if string.is_ascii() {
+        let mut data = Vec::with_capacity(string.len() + 1);
+        data.extend_from_slice(string.as_bytes());
+        data.push(0);
+        (data.len() as i32, data)
+    } else {
+        let data: Vec<u8> = string
+            .encode_utf16()
+            .chain(std::iter::once(0))
+            .flat_map(|c| c.to_le_bytes().to_vec())
+            .collect();
+        (-(data.len() as i32 / 2), data)
+    }
+}
+
+fn decode_narrow(data: Cow<[u8]>) -> Cow<str> {
+    let len = match data.last() {
+        Some(0) => data.len() - 1,
+        _ => data.len(),
+    };
+    match data {
+        Cow::Borrowed(bytes) => match std::str::from_utf8(&bytes[..len]) {
+            Ok(string) => Cow::Borrowed(string),
+            Err(_) => Cow::Owned(latin1(&bytes[..len])),
+        },
+        Cow::Owned(mut bytes) => {
+            bytes.truncate(len);
+            match String::from_utf8(bytes) {
+                Ok(string) => Cow::Owned(string),
+                Err(e) => Cow::Owned(latin1(e.as_bytes())),
+            }
+        }
+    }
+}
+
+/// Single byte strings are written in the ANSI code page, so anything that
+/// isn't valid UTF-8 is treated as Latin-1.
+fn latin1(data: &[u8]) -> String {
+    data.iter().map(|b| *b as char).collect()
+}
+
+fn decode_utf16(data: &[u8]) -> Result<String, String> {
+    let mut units: Vec<u16> = data
+        .chunks_exact(2)
+        .map(|c| u16::from_le_bytes([c[0], c[1]]))
+        .collect();
+    if units.last() == Some(&0) {
+        units.pop();
+    }
+    String::from_utf16(&units).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::{ArrayReader, Reader};
+    use std::io::Cursor;
+
+    fn wide(string: &str) -> Vec<u8> {
+        string
+            .encode_utf16()
+            .chain(std::iter::once(0))
+            .flat_map(|c| c.to_le_bytes().to_vec())
+            .collect()
+    }
+
+    #[test]
+    fn empty() {
+        assert_eq!(decode(0, Cow::Borrowed(&[])).unwrap(), "");
+        assert_eq!(decode(1, Cow::Borrowed(&[0])).unwrap(), "");
+        assert_eq!(decode(-1, Cow::Borrowed(&[0, 0])).unwrap(), "");
+    }
+
+    #[test]
+    fn null_terminated() {
+        let decoded = decode(4, Cow::Borrowed(b"Rex\0")).unwrap();
+        assert_eq!(decoded, "Rex");
+        assert!(matches!(decoded, Cow::Borrowed(_)));
+
+        let decoded = decode(4, Cow::Owned(b"Rex\0".to_vec())).unwrap();
+        assert_eq!(decoded, "Rex");
+    }
+
+    #[test]
+    fn missing_terminator() {
+        assert_eq!(decode(3, Cow::Borrowed(b"Rex")).unwrap(), "Rex");
+    }
+
+    #[test]
+    fn latin1_fallback() {
+        assert_eq!(decode(5, Cow::Borrowed(b"Caf\xe9\0")).unwrap(), "Café");
+        assert_eq!(
+            decode(5, Cow::Owned(b"Caf\xe9\0".to_vec())).unwrap(),
+            "Café"
+        );
+    }
+
+    #[test]
+    fn utf16() {
+        let data = wide("Ptéra");
+        assert_eq!(decode(-6, Cow::Owned(data)).unwrap(), "Ptéra");
+    }
+
+    #[test]
+    fn utf16_surrogate_pair() {
+        let data = wide("Rex 🦖");
+        let size = -(data.len() as i32 / 2);
+        assert_eq!(size, -7);
+        assert_eq!(decode(size, Cow::Borrowed(&data)).unwrap(), "Rex 🦖");
+    }
+
+    #[test]
+    fn utf16_lone_surrogate() {
+        let data = [0x3d, 0xd8, 0, 0];
+        assert!(decode(-2, Cow::Borrowed(&data)).is_err());
+    }
+
+    #[test]
+    fn utf16_odd_length() {
+        // Three characters plus the terminator, starting at an odd address
+        let mut data = vec![0xff];
+        data.extend(wide("Dé!"));
+        assert_eq!(decode(-4, Cow::Borrowed(&data[1..])).unwrap(), "Dé!");
+
+        // A truncated buffer with an odd number of bytes
+        assert!(decode(-4, Cow::Borrowed(&data[1..8])).is_err());
+    }
+
+    #[test]
+    fn encoded() {
+        assert_eq!(encode(""), (0, vec![]));
+        assert_eq!(encode("Rex"), (4, b"Rex\0".to_vec()));
+        assert_eq!(encode("Ptéra"), (-6, wide("Ptéra")));
+        assert_eq!(encode("Rex 🦖"), (-7, wide("Rex 🦖")));
+    }
+
+    #[test]
+    fn round_trip() {
+        for string in &["", "Rex", "Café", "Rex 🦖"] {
+            let (size, data) = encode(string);
+            assert_eq!(byte_len(size), data.len());
+            assert_eq!(decode(size, Cow::Owned(data)).unwrap(), *string);
+        }
+    }
+
+    #[test]
+    fn byte_lengths() {
+        assert_eq!(byte_len(0), 0);
+        assert_eq!(byte_len(5), 5);
+        assert_eq!(byte_len(-5), 10);
+    }
+
+    #[test]
+    fn readers_agree() {
+        let mut data = (-6i32).to_le_bytes().to_vec();
+        data.extend(wide("Ptéra"));
+        data.extend(4i32.to_le_bytes().iter());
+        data.extend(b"Rex\0");
+
+        let mut array = ArrayReader::new(&data);
+        assert_eq!(array.read_fstring().unwrap(), "Ptéra");
+        assert_eq!(array.read_fstring().unwrap(), "Rex");
+        assert!(array.read_fstring().is_err());
+
+        let mut cursor = Cursor::new(&data);
+        assert_eq!(cursor.read_str().unwrap(), "Ptéra");
+        assert_eq!(cursor.read_str().unwrap(), "Rex");
+        assert!(cursor.read_str().is_err());
+    }
+}
@@ -1,31 +1,150 @@
+pub mod fstring;
 mod memory;
 mod mmap;
+mod writer;
 
+use crate::error::{ArkError, Result};
 pub use memory::ArrayReader;
 pub use mmap::MMappedReader;
 use serde::Serialize;
-use std::io::{Read, Result, Seek};
+use std::borrow::Cow;
+use std::io::{self, Error, ErrorKind, Read, Seek, SeekFrom};
+pub use writer::Writer;
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub struct Name {
     pub id: usize,
     pub instance: u32,
 }
 
+pub(crate) fn seek_offset(current: usize, len: usize, pos: SeekFrom) -> io::Result<usize> {
+    let target = match pos {
+        SeekFrom::Current(ind) => current as i64 + ind,
+        SeekFrom::End(ind) => len as i64 + ind,
+        SeekFrom::Start(ind) => ind as i64,
+    };
+    if target < 0 {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("Seek to negative offset {}", target),
+        ));
+    }
+    Ok(target as usize)
+}
+
+macro_rules! read_le {
+    ($reader:ident, $type:ty, $field:expr) => {{
+        let mut buf = [0; std::mem::size_of::<$type>()];
+        fill($reader, &mut buf, $field)?;
+        Ok(<$type>::from_le_bytes(buf))
+    }};
+}
+
+fn fill<R: Read + Seek + ?Sized>(
+    reader: &mut R,
+    buf: &mut [u8],
+    field: &'static str,
+) -> Result<()> {
+    match reader.read_exact(buf) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == ErrorKind::UnexpectedEof => Err(ArkError::UnexpectedEof {
+            offset: reader.stream_position()?,
+            field,
+        }),
+        Err(e) => Err(ArkError::Io(e)),
+    }
+}
+
+/// Primitive decoders for the little endian Unreal archive format.
+///
+/// Implemented for every `Read + Seek` type, so the same parsing code works
+/// against a memory map, an in memory buffer or a `BufReader<File>`.
 pub trait Reader: Seek + Read {
-    fn read_bool(&mut self) -> Result<bool>;
-    fn read_f32(&mut self) -> Result<f32>;
-    fn read_f64(&mut self) -> Result<f64>;
-    fn read_i16(&mut self) -> Result<i16>;
-    fn read_i32(&mut self) -> Result<i32>;
-    fn read_i64(&mut self) -> Result<i64>;
-    fn read_i8(&mut self) -> Result<i8>;
-    fn read_str(&mut self) -> Result<String>;
-    fn read_u128(&mut self) -> Result<u128>;
-    fn read_u16(&mut self) -> Result<u16>;
-    fn read_u32(&mut self) -> Result<u32>;
-    fn read_u64(&mut self) -> Result<u64>;
-    fn read_u8(&mut self) -> Result<u8>;
+    fn read_bool(&mut self) -> Result<bool> {
+        Ok(self.read_i32()? == 1)
+    }
+
+    fn read_bytes(&mut self, len: usize) -> Result<Vec<u8>> {
+        // The length comes from the file, so it's only trusted as far as the
+        // data actually goes
+        let mut buf = Vec::with_capacity(len.min(4096));
+        (&mut *self).take(len as u64).read_to_end(&mut buf)?;
+        if buf.len() < len {
+            return Err(ArkError::UnexpectedEof {
+                offset: self.stream_position()?,
+                field: "bytes",
+            });
+        }
+        Ok(buf)
+    }
+
+    fn read_f32(&mut self) -> Result<f32> {
+        read_le!(self, f32, "f32")
+    }
+
+    fn read_f64(&mut self) -> Result<f64> {
+        read_le!(self, f64, "f64")
+    }
+
+    fn read_i16(&mut self) -> Result<i16> {
+        read_le!(self, i16, "i16")
+    }
+
+    fn read_i32(&mut self) -> Result<i32> {
+        read_le!(self, i32, "i32")
+    }
+
+    fn read_i64(&mut self) -> Result<i64> {
+        read_le!(self, i64, "i64")
+    }
+
+    fn read_i8(&mut self) -> Result<i8> {
+        read_le!(self, i8, "i8")
+    }
+
+    /// Reads an FString with `fstring::decode`. A `Reader` has no buffer to
+    /// lend its data from, so the string is always owned; `Source` borrows
+    /// strings from data held in memory.
+    fn read_str(&mut self) -> Result<String> {
+        let size = self.read_i32()?;
+        let bytes = fstring::byte_len(size);
+        let mut buf = Vec::with_capacity(bytes.min(4096));
+        (&mut *self).take(bytes as u64).read_to_end(&mut buf)?;
+        if buf.len() < bytes {
+            return Err(ArkError::UnexpectedEof {
+                offset: self.stream_position()? - buf.len() as u64,
+                field: "string",
+            });
+        }
+        let decoded = fstring::decode(size, Cow::Owned(buf));
+        match decoded {
+            Ok(string) => Ok(string.into_owned()),
+            Err(message) => Err(ArkError::InvalidString {
+                offset: self.stream_position()?.saturating_sub(bytes as u64 + 4),
+                message,
+            }),
+        }
+    }
+
+    fn read_u128(&mut self) -> Result<u128> {
+        read_le!(self, u128, "u128")
+    }
+
+    fn read_u16(&mut self) -> Result<u16> {
+        read_le!(self, u16, "u16")
+    }
+
+    fn read_u32(&mut self) -> Result<u32> {
+        read_le!(self, u32, "u32")
+    }
+
+    fn read_u64(&mut self) -> Result<u64> {
+        read_le!(self, u64, "u64")
+    }
+
+    fn read_u8(&mut self) -> Result<u8> {
+        read_le!(self, u8, "u8")
+    }
 
     fn read_name(&mut self) -> Result<Name> {
         Ok(Name {
@@ -34,5 +153,140 @@ pub trait Reader: Seek + Read {
         })
     }
 
-    fn skip_str(&mut self) -> Result<()>;
+    fn skip_str(&mut self) -> Result<()> {
+        let size = self.read_i32()?;
+        self.seek(SeekFrom::Current(fstring::byte_len(size) as i64))?;
+        Ok(())
+    }
+}
+
+impl<T: Read + Seek> Reader for T {}
+
+/// A `Reader` whose strings may borrow from the data being parsed.
+pub trait Source<'a>: Reader {
+    fn read_string(&mut self) -> Result<Cow<'a, str>>;
+
+    fn read_data(&mut self, len: usize) -> Result<Cow<'a, [u8]>>;
+}
+
+impl<'a> Source<'a> for ArrayReader<'a> {
+    fn read_string(&mut self) -> Result<Cow<'a, str>> {
+        self.read_fstring()
+    }
+
+    fn read_data(&mut self, len: usize) -> Result<Cow<'a, [u8]>> {
+        ArrayReader::read_data(self, len).map(Cow::Borrowed)
+    }
+}
+
+/// Adapts any `Reader` into a `Source` that copies every string it reads.
+pub struct Owned<'r>(pub &'r mut dyn Reader);
+
+impl<'r> Read for Owned<'r> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.read(buf)
+    }
+
+    fn read_exact(&mut self, buf: &mut [u8]) -> io::Result<()> {
+        self.0.read_exact(buf)
+    }
+}
+
+impl<'r> Seek for Owned<'r> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.0.seek(pos)
+    }
+
+    fn stream_position(&mut self) -> io::Result<u64> {
+        self.0.stream_position()
+    }
+}
+
+impl<'a, 'r> Source<'a> for Owned<'r> {
+    fn read_string(&mut self) -> Result<Cow<'a, str>> {
+        Ok(Cow::Owned(self.0.read_str()?))
+    }
+
+    fn read_data(&mut self, len: usize) -> Result<Cow<'a, [u8]>> {
+        Ok(Cow::Owned(self.0.read_bytes(len)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufReader, Cursor};
+
+    fn fixture() -> Vec<u8> {
+        let mut data = Vec::new();
+        data.write_i32(7).unwrap();
+        data.write_str("Rex").unwrap();
+        data.write_str("Ptéra").unwrap();
+        data.write_u128(0x1234).unwrap();
+        data.write_name(&Name { id: 3, instance: 1 }).unwrap();
+        data.write_f64(1.5).unwrap();
+        data.write_bytes(&[1, 2, 3]).unwrap();
+        data.write_bool(true).unwrap();
+        data.write_str("Dodo").unwrap();
+        data
+    }
+
+    fn read_all<'a>(file: &mut dyn Source<'a>) -> Result<()> {
+        assert_eq!(file.read_i32()?, 7);
+        assert_eq!(file.read_string()?, "Rex");
+        assert_eq!(file.read_string()?, "Ptéra");
+        assert_eq!(file.read_u128()?, 0x1234);
+        assert_eq!(file.read_name()?, Name { id: 3, instance: 1 });
+        assert_eq!(file.read_f64()?, 1.5);
+        assert_eq!(&*file.read_data(3)?, &[1, 2, 3]);
+        assert!(file.read_bool()?);
+        assert_eq!(file.read_str()?, "Dodo");
+        Ok(())
+    }
+
+    /// Every reader reports running out of data as an error rather than
+    /// panicking, wherever the data is cut.
+    #[test]
+    fn truncated() {
+        let data = fixture();
+        let path = std::env::temp_dir().join(format!("arksavefile-io-{}", std::process::id()));
+        for len in 0..=data.len() {
+            let cut = &data[..len];
+            let mut buffered = BufReader::new(Cursor::new(cut));
+            let mut results = vec![
+                read_all(&mut ArrayReader::new(cut)),
+                read_all(&mut Owned(&mut Cursor::new(cut))),
+                read_all(&mut Owned(&mut buffered)),
+            ];
+            // Empty files can't be mapped
+            if len > 0 {
+                std::fs::write(&path, cut).unwrap();
+                let mut mmap = MMappedReader::open(path.to_str().unwrap()).unwrap();
+                results.push(read_all(&mut Owned(&mut mmap)));
+            }
+            for result in results {
+                match result {
+                    Ok(()) => assert_eq!(len, data.len()),
+                    Err(e) => assert!(
+                        len < data.len() && matches!(e, ArkError::UnexpectedEof { .. }),
+                        "{} at {}",
+                        e,
+                        len
+                    ),
+                }
+            }
+        }
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn huge_lengths() {
+        let mut data = Vec::new();
+        data.write_i32(i32::MAX).unwrap();
+        data.write_bytes(b"Rex").unwrap();
+        assert!(ArrayReader::new(&data).read_fstring().is_err());
+        assert!(Cursor::new(&data).read_str().is_err());
+        assert!(Cursor::new(&data).read_bytes(usize::MAX).is_err());
+        assert!(ArrayReader::new(&data).read_data(usize::MAX).is_err());
+    }
 }
@@ -0,0 +1,83 @@
+use super::{fstring, Name};
+use crate::Result;
+use std::io::Write;
+
+macro_rules! write_le {
+    ($writer:ident, $value:expr) => {{
+        $writer.write_all(&$value.to_le_bytes())?;
+        Ok(())
+    }};
+}
+
+/// Primitive encoders for the little endian Unreal archive format, the
+/// counterpart of `Reader`.
+///
+/// Implemented for every `Write` type, such as a `Vec<u8>` or a
+/// `BufWriter<File>`.
+pub trait Writer: Write {
+    fn write_bool(&mut self, value: bool) -> Result<()> {
+        self.write_i32(value as i32)
+    }
+
+    fn write_bytes(&mut self, data: &[u8]) -> Result<()> {
+        self.write_all(data)?;
+        Ok(())
+    }
+
+    fn write_f32(&mut self, value: f32) -> Result<()> {
+        write_le!(self, value)
+    }
+
+    fn write_f64(&mut self, value: f64) -> Result<()> {
+        write_le!(self, value)
+    }
+
+    fn write_i16(&mut self, value: i16) -> Result<()> {
+        write_le!(self, value)
+    }
+
+    fn write_i32(&mut self, value: i32) -> Result<()> {
+        write_le!(self, value)
+    }
+
+    fn write_i64(&mut self, value: i64) -> Result<()> {
+        write_le!(self, value)
+    }
+
+    fn write_i8(&mut self, value: i8) -> Result<()> {
+        write_le!(self, value)
+    }
+
+    fn write_str(&mut self, value: &str) -> Result<()> {
+        let (size, data) = fstring::encode(value);
+        self.write_i32(size)?;
+        self.write_bytes(&data)
+    }
+
+    fn write_u128(&mut self, value: u128) -> Result<()> {
+        write_le!(self, value)
+    }
+
+    fn write_u16(&mut self, value: u16) -> Result<()> {
+        write_le!(self, value)
+    }
+
+    fn write_u32(&mut self, value: u32) -> Result<()> {
+        write_le!(self, value)
+    }
+
+    fn write_u64(&mut self, value: u64) -> Result<()> {
+        write_le!(self, value)
+    }
+
+    fn write_u8(&mut self, value: u8) -> Result<()> {
+        write_le!(self, value)
+    }
+
+    fn write_name(&mut self, name: &Name) -> Result<()> {
+        self.write_u32(name.id as u32)?;
+        self.write_u32(name.instance)
+    }
+}
+
+impl<T: Write> Writer for T {}
@@ -1,15 +1,23 @@
+mod error;
 mod file;
 mod io;
 mod object;
 mod properties;
 
-#[macro_use]
-extern crate arrayref;
+#[cfg(test)]
+mod fixture;
+
 extern crate base64;
 extern crate lazy_static;
 extern crate paste;
 extern crate serde;
 
-pub use file::ArkParser;
-pub use io::MMappedReader;
-pub use object::{Entry, Location, Object, Type};
+pub use error::{ArkError, ObjectContext, Result};
+pub use file::{
+    ArkParser, ArkWriter, AsaParser, ClusterParser, CreatureContainer, CreatureContainers,
+    DataFileObjects, EmbeddedData, HibernationEntry, Painting, PlayerProfile, ProfileParser,
+    SaveHeader, Tribe, TribeAlliance, TribeMember, TribeParser, UploadedCharacter,
+    UploadedCreature, UploadedItem,
+};
+pub use io::{ArrayReader, MMappedReader, Reader, Writer};
+pub use object::{Entry, Location, Object, Rotator, Type};
@@ -1,6 +1,5 @@
 use arksavefile::{ArkParser, Entry, MMappedReader, Type};
 use serde::Serialize;
-use serde_json;
 use std::env;
 use std::fs;
 use std::fs::File;
@@ -44,8 +43,11 @@ struct Baby<'a> {
 fn main() -> Result<()> {
     let args: Vec<String> = env::args().collect();
     if args.len() == 2 {
-        let mut file = MMappedReader::open(&args[1])?;
-        let file = ArkParser::read(&mut file)?;
+        let mmap = MMappedReader::open(&args[1])?;
+        let file = ArkParser::read_borrowed(mmap.as_slice())?;
+        for error in &file.container_errors {
+            eprintln!("Skipped a creature container: {}", error);
+        }
         fs::create_dir_all(&file.map)?;
         write_wild(&file)?;
         write_tamed(&file)?;
@@ -91,8 +93,7 @@ fn write_wild(file: &ArkParser) -> Result<()> {
                     .unwrap()
                     .properties
                     .get_i32(base_level)
-                    .or(Some(1))
-                    .unwrap(),
+                    .unwrap_or(1),
                 base_stats: stats(o, base_levels),
             }
         })
@@ -186,7 +187,10 @@ fn write_nursery(file: &ArkParser) -> Result<()> {
 }
 
 fn write_cryopods(file: &ArkParser) -> Result<()> {
-    let cryopod = *file.get_name_id("PrimalItem_WeaponEmptyCryopod_C").unwrap();
+    let cryopod = match file.get_name_id("PrimalItem_WeaponEmptyCryopod_C") {
+        Some(id) => *id,
+        None => return Ok(()),
+    };
 
     let entries: Vec<&Entry> = file
         .entries()
@@ -1,20 +1,53 @@
-use crate::io::Reader;
+use crate::io::{Reader, Writer};
+use crate::Result;
 use serde::Serialize;
-use std::io::{Result, SeekFrom};
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Location {
     pub x: f32,
     pub y: f32,
     pub z: f32,
+    pub rotation: Rotator,
+}
+
+/// Rotation in degrees, in the order Unreal stores it.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize)]
+pub struct Rotator {
+    pub pitch: f32,
+    pub yaw: f32,
+    pub roll: f32,
 }
 
 impl Location {
     pub fn read(file: &mut dyn Reader) -> Result<Self> {
-        let x = file.read_f32()?;
-        let y = file.read_f32()?;
-        let z = file.read_f32()?;
-        file.seek(SeekFrom::Current(12))?;
-        Ok(Location { x, y, z })
+        Ok(Location {
+            x: file.read_f32()?,
+            y: file.read_f32()?,
+            z: file.read_f32()?,
+            rotation: Rotator::read(file)?,
+        })
+    }
+
+    pub fn write(&self, file: &mut dyn Writer) -> Result<()> {
+        file.write_f32(self.x)?;
+        file.write_f32(self.y)?;
+        file.write_f32(self.z)?;
+        self.rotation.write(file)
+    }
+}
+
+impl Rotator {
+    pub fn read(file: &mut dyn Reader) -> Result<Self> {
+        Ok(Rotator {
+            pitch: file.read_f32()?,
+            yaw: file.read_f32()?,
+            roll: file.read_f32()?,
+        })
+    }
+
+    pub fn write(&self, file: &mut dyn Writer) -> Result<()> {
+        file.write_f32(self.pitch)?;
+        file.write_f32(self.yaw)?;
+        file.write_f32(self.roll)
     }
 }
@@ -1,24 +1,64 @@
 mod location;
 mod names;
+#[allow(clippy::module_inception)]
 mod object;
 mod serialize;
 use super::properties::Properties;
+use crate::file::HibernationEntry;
 pub use crate::io::Name;
-pub use location::Location;
+pub use location::{Location, Rotator};
 pub use names::Names;
+pub(crate) use names::{NameFormat, NameStr};
+pub(crate) use object::ObjectRecord;
 pub use object::{Object, Type};
 use std::rc::Rc;
 
-pub struct Entry {
+pub struct Entry<'a> {
     pub object_type: Type,
-    pub(crate) objects: Rc<Vec<Object>>,
+    pub(crate) objects: Rc<Vec<Object<'a>>>,
     pub(crate) object: usize,
     pub(crate) inventory: Option<usize>,
     pub(crate) status: Option<usize>,
 }
 
-impl Entry {
-    pub fn inventory_component(&self) -> Option<&Object> {
+impl<'a> Entry<'a> {
+    pub(crate) fn new(objects: &Rc<Vec<Object<'a>>>, object: usize) -> Self {
+        let o = &objects[object];
+        Entry {
+            object_type: o.object_type,
+            objects: objects.clone(),
+            object,
+            inventory: o.inventory_component,
+            status: o.status_component,
+        }
+    }
+
+    /// The cryopod item a creature was stored in.
+    pub fn cryopod(&self) -> Option<&Object<'a>> {
+        Some(&self.objects[self.objects[self.object].cryopod?])
+    }
+
+    /// The inventory holding the cryopod a creature was stored in.
+    pub fn cryopod_inventory(&self) -> Option<&Object<'a>> {
+        let cryopod = self.cryopod()?;
+        let owner = cryopod.names.get_name_id("OwnerInventory")?;
+        let index = cryopod.properties.get_i32(owner)?;
+        self.objects.get(index as usize)
+    }
+
+    /// The hibernation cell the object was stored in, if the server had
+    /// unloaded it when the map was saved.
+    pub fn hibernation(&self) -> Option<&HibernationEntry> {
+        self.objects[self.object].hibernation.as_deref()
+    }
+
+    /// The index of the object, as used by object references and for
+    /// editing it through the parser.
+    pub fn index(&self) -> usize {
+        self.object
+    }
+
+    pub fn inventory_component(&self) -> Option<&Object<'a>> {
         match self.inventory {
             Some(index) => Some(&self.objects[index]),
             None => None,
@@ -36,11 +76,19 @@ impl Entry {
         &self.objects[self.object].name
     }
 
-    pub fn properties(&self) -> &Properties {
+    pub fn properties(&self) -> &Properties<'a> {
         &self.objects[self.object].properties
     }
 
-    pub fn status_component(&self) -> Option<&Object> {
+    /// The team the object belongs to: a tribe id, a player id for players
+    /// without a tribe, or a low number for wild creatures.
+    pub fn targeting_team(&self) -> Option<i32> {
+        let object = &self.objects[self.object];
+        let team = object.names.get_name_id("TargetingTeam")?;
+        object.properties.get_i32(team)
+    }
+
+    pub fn status_component(&self) -> Option<&Object<'a>> {
         match self.status {
             Some(index) => Some(&self.objects[index]),
             None => None,
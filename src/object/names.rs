@@ -1,126 +1,391 @@
-use crate::io::Reader;
+use crate::io::{Name, Reader, Source};
+use crate::{ArkError, Result};
+use std::borrow::{Borrow, Cow};
 use std::collections::hash_map::HashMap;
-use std::io::{Result, SeekFrom};
-use std::ops::Index;
+use std::hash::{Hash, Hasher};
+use std::io::SeekFrom;
+use std::ops::{Deref, Index};
 use std::rc::Rc;
 
-pub struct Names {
-    names: Vec<Rc<String>>,
-    lookup: HashMap<Rc<String>, usize>,
+pub struct Names<'a> {
+    names: Vec<NameStr<'a>>,
+    lookup: HashMap<NameStr<'a>, usize>,
     pub array_property: usize,
     pub bool_property: usize,
+    pub box_property: usize,
     pub byte_property: usize,
     pub color_property: usize,
     pub double_property: usize,
     pub float_property: usize,
+    pub guid_property: usize,
     pub int16_property: usize,
     pub int8_property: usize,
+    pub int_point_property: usize,
+    pub int_vector_property: usize,
     pub int_property: usize,
+    pub item_netid_property: usize,
     pub linear_color_property: usize,
+    pub map_property: usize,
     pub name_property: usize,
     pub object_property: usize,
     pub quat_property: usize,
     pub rotator_property: usize,
+    pub set_property: usize,
     pub str_property: usize,
     pub struct_property: usize,
     pub text_property: usize,
+    pub transform_property: usize,
     pub uint16_property: usize,
     pub uint32_property: usize,
     pub uint64_property: usize,
     pub unique_netid_property: usize,
     pub vector_property: usize,
     pub vector2d_property: usize,
+    pub(crate) format: NameFormat,
+    /// Number of names read from the save's own table, later names were
+    /// added from other sections.
+    pub(crate) table_len: usize,
+    /// Where the save's names table ends.
+    pub(crate) table_end: u64,
+    /// Properties use the Unreal Engine 5 encoding of Ark: Survival Ascended.
+    pub(crate) asa: bool,
+    /// Object properties are saved as a bare index, as in older saves,
+    /// rather than an index or name following its kind.
+    pub(crate) short_object_references: bool,
 }
 
-impl Names {
-    pub fn new(file: &mut dyn Reader, names_offset: u64) -> Result<Self> {
-        let current_pos = file.seek(SeekFrom::Current(0))?;
+/// A name in the table, either borrowed from the save or copied out of it
+/// once and shared by the table, its lookup and properties.
+#[derive(Debug, Clone)]
+pub(crate) enum NameStr<'a> {
+    Borrowed(&'a str),
+    Shared(Rc<str>),
+}
+
+impl<'a> From<Cow<'a, str>> for NameStr<'a> {
+    fn from(name: Cow<'a, str>) -> Self {
+        match name {
+            Cow::Borrowed(name) => NameStr::Borrowed(name),
+            Cow::Owned(name) => NameStr::Shared(Rc::from(name)),
+        }
+    }
+}
+
+impl<'a> Deref for NameStr<'a> {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        match self {
+            NameStr::Borrowed(name) => name,
+            NameStr::Shared(name) => name,
+        }
+    }
+}
+
+impl<'a> Borrow<str> for NameStr<'a> {
+    fn borrow(&self) -> &str {
+        self
+    }
+}
+
+// Hashed and compared as the string, so it can be looked up by one
+impl<'a> Hash for NameStr<'a> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (**self).hash(state)
+    }
+}
+
+impl<'a> PartialEq for NameStr<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl<'a> Eq for NameStr<'a> {}
+
+/// How names are encoded in the section currently being read.
+pub(crate) enum NameFormat {
+    /// An index into this table followed by an instance number.
+    Indexed,
+    /// An index into a section's own table, which has been interned into this
+    /// one. Instance numbers are part of the name in the section's table.
+    Remapped(Vec<(usize, u32)>),
+    /// An id from a names table keyed by arbitrary ids, which has been
+    /// interned into this one, followed by an instance number.
+    Mapped(HashMap<u32, usize>),
+    /// The name as a string with its instance number as a suffix, as used
+    /// where there is no names table such as cryopod payloads.
+    Strings,
+}
+
+impl<'a> Names<'a> {
+    pub fn new(file: &mut dyn Source<'a>, names_offset: u64) -> Result<Self> {
+        let current_pos = file.stream_position()?;
         file.seek(SeekFrom::Start(names_offset))?;
         let name_count = file.read_i32()?;
-        let capacity = name_count as usize + 1;
+        let capacity = (name_count.max(0) as usize).min(4096) + 1;
         let mut names = Vec::with_capacity(capacity);
         let mut lookup = HashMap::with_capacity(capacity);
         // Name indexes start at 1, adding a dummy will align the indexes
-        names.push(Rc::new(String::from("-----")));
+        names.push(NameStr::Borrowed("-----"));
         for i in 1..=name_count {
-            let name = Rc::new(file.read_str()?);
+            let name = NameStr::from(file.read_string()?);
             names.push(name.clone());
             lookup.insert(name, i as usize);
         }
+        let table_end = file.stream_position()?;
         file.seek(SeekFrom::Start(current_pos))?;
+        let mut names = Names::from_table(names, lookup, NameFormat::Indexed);
+        names.table_len = name_count.max(0) as usize;
+        names.table_end = table_end;
+        Ok(names)
+    }
+
+    /// An empty table for files that store names as strings, such as
+    /// profiles and tribes.
+    pub fn with_strings() -> Self {
+        Names::from_table(
+            vec![NameStr::Borrowed("-----")],
+            HashMap::new(),
+            NameFormat::Strings,
+        )
+    }
 
-        let array_property = name_id(&lookup, "ArrayProperty");
-        let bool_property = name_id(&lookup, "BoolProperty");
-        let byte_property = name_id(&lookup, "ByteProperty");
-        let color_property = name_id(&lookup, "Color");
-        let double_property = name_id(&lookup, "DoubleProperty");
-        let float_property = name_id(&lookup, "FloatProperty");
-        let int16_property = name_id(&lookup, "Int16Property");
-        let int8_property = name_id(&lookup, "Int8Property");
-        let int_property = name_id(&lookup, "IntProperty");
-        let linear_color_property = name_id(&lookup, "LinearColor");
-        let name_property = name_id(&lookup, "NameProperty");
-        let object_property = name_id(&lookup, "ObjectProperty");
-        let quat_property = name_id(&lookup, "Quat");
-        let rotator_property = name_id(&lookup, "Rotator");
-        let str_property = name_id(&lookup, "StrProperty");
-        let struct_property = name_id(&lookup, "StructProperty");
-        let text_property = name_id(&lookup, "TextProperty");
-        let uint16_property = name_id(&lookup, "UInt16Property");
-        let uint32_property = name_id(&lookup, "UInt32Property");
-        let uint64_property = name_id(&lookup, "UInt64Property");
-        let unique_netid_property = name_id(&lookup, "UniqueNetIdRepl");
-        let vector_property = name_id(&lookup, "Vector");
-        let vector2d_property = name_id(&lookup, "Vector2D");
-
-        Ok(Names {
+    fn from_table(
+        names: Vec<NameStr<'a>>,
+        lookup: HashMap<NameStr<'a>, usize>,
+        format: NameFormat,
+    ) -> Self {
+        let mut names = Names {
             names,
             lookup,
-            array_property,
-            bool_property,
-            byte_property,
-            color_property,
-            double_property,
-            float_property,
-            int16_property,
-            int8_property,
-            int_property,
-            linear_color_property,
-            name_property,
-            object_property,
-            quat_property,
-            rotator_property,
-            str_property,
-            struct_property,
-            text_property,
-            uint16_property,
-            uint32_property,
-            uint64_property,
-            unique_netid_property,
-            vector_property,
-            vector2d_property,
-        })
+            format,
+            table_len: 0,
+            table_end: 0,
+            asa: false,
+            short_object_references: false,
+            array_property: 0,
+            bool_property: 0,
+            box_property: 0,
+            byte_property: 0,
+            color_property: 0,
+            double_property: 0,
+            float_property: 0,
+            guid_property: 0,
+            int16_property: 0,
+            int8_property: 0,
+            int_point_property: 0,
+            int_vector_property: 0,
+            int_property: 0,
+            item_netid_property: 0,
+            linear_color_property: 0,
+            map_property: 0,
+            name_property: 0,
+            object_property: 0,
+            quat_property: 0,
+            rotator_property: 0,
+            set_property: 0,
+            str_property: 0,
+            struct_property: 0,
+            text_property: 0,
+            transform_property: 0,
+            uint16_property: 0,
+            uint32_property: 0,
+            uint64_property: 0,
+            unique_netid_property: 0,
+            vector_property: 0,
+            vector2d_property: 0,
+        };
+        names.update_known_ids();
+        names
+    }
+
+    /// Reads a name in the current `format`, returning its id in this table.
+    pub(crate) fn read_name(&mut self, file: &mut dyn Reader) -> Result<Name> {
+        let (index, (id, instance)) = match &self.format {
+            NameFormat::Indexed => {
+                let index = file.read_u32()? as usize;
+                (index, (index, file.read_u32()?))
+            }
+            NameFormat::Remapped(ids) => {
+                let index = file.read_u32()? as usize;
+                (index, *ids.get(index).unwrap_or(&(self.names.len(), 0)))
+            }
+            NameFormat::Mapped(ids) => {
+                let index = file.read_u32()?;
+                let id = *ids.get(&index).unwrap_or(&self.names.len());
+                (index as usize, (id, file.read_u32()?))
+            }
+            NameFormat::Strings => {
+                let name = Cow::Owned(file.read_str()?);
+                return Ok(self.intern_name(name));
+            }
+        };
+        if id >= self.names.len() {
+            return Err(ArkError::InvalidData {
+                offset: file.stream_position()? - 4,
+                message: format!("Name index {} out of range", index),
+            });
+        }
+        Ok(Name { id, instance })
+    }
+
+    fn intern_name(&mut self, name: Cow<'a, str>) -> Name {
+        let (id, instance) = self.intern_with_instance(name);
+        Name { id, instance }
+    }
+
+    /// Adds a name with its instance number as a suffix, as found in sections
+    /// with their own names table, returning its id and instance.
+    pub(crate) fn intern_with_instance(&mut self, name: Cow<'a, str>) -> (usize, u32) {
+        match split_instance(&name) {
+            Some((base, instance)) => {
+                let base = match name {
+                    Cow::Borrowed(name) => Cow::Borrowed(&name[..base]),
+                    Cow::Owned(mut name) => {
+                        name.truncate(base);
+                        Cow::Owned(name)
+                    }
+                };
+                (self.intern(base), instance)
+            }
+            None => (self.intern(name), 0),
+        }
+    }
+
+    pub(crate) fn intern(&mut self, name: Cow<'a, str>) -> usize {
+        if let Some(id) = self.lookup.get(&*name) {
+            return *id;
+        }
+        let id = self.names.len();
+        let name = NameStr::from(name);
+        self.names.push(name.clone());
+        self.lookup.insert(name, id);
+        self.update_known_ids();
+        id
+    }
+
+    fn update_known_ids(&mut self) {
+        let lookup = &self.lookup;
+        self.array_property = name_id(lookup, "ArrayProperty");
+        self.bool_property = name_id(lookup, "BoolProperty");
+        self.box_property = name_id(lookup, "Box");
+        self.byte_property = name_id(lookup, "ByteProperty");
+        self.color_property = name_id(lookup, "Color");
+        self.double_property = name_id(lookup, "DoubleProperty");
+        self.float_property = name_id(lookup, "FloatProperty");
+        self.guid_property = name_id(lookup, "Guid");
+        self.int16_property = name_id(lookup, "Int16Property");
+        self.int8_property = name_id(lookup, "Int8Property");
+        self.int_point_property = name_id(lookup, "IntPoint");
+        self.int_vector_property = name_id(lookup, "IntVector");
+        self.int_property = name_id(lookup, "IntProperty");
+        self.item_netid_property = name_id(lookup, "ItemNetID");
+        self.linear_color_property = name_id(lookup, "LinearColor");
+        self.map_property = name_id(lookup, "MapProperty");
+        self.name_property = name_id(lookup, "NameProperty");
+        self.object_property = name_id(lookup, "ObjectProperty");
+        self.quat_property = name_id(lookup, "Quat");
+        self.rotator_property = name_id(lookup, "Rotator");
+        self.set_property = name_id(lookup, "SetProperty");
+        self.str_property = name_id(lookup, "StrProperty");
+        self.struct_property = name_id(lookup, "StructProperty");
+        self.text_property = name_id(lookup, "TextProperty");
+        self.transform_property = name_id(lookup, "Transform");
+        self.uint16_property = name_id(lookup, "UInt16Property");
+        self.uint32_property = name_id(lookup, "UInt32Property");
+        self.uint64_property = name_id(lookup, "UInt64Property");
+        self.unique_netid_property = name_id(lookup, "UniqueNetIdRepl");
+        self.vector_property = name_id(lookup, "Vector");
+        self.vector2d_property = name_id(lookup, "Vector2D");
     }
 
     pub fn get_name_id(&self, name: &str) -> Option<&usize> {
-        self.lookup.get(&Rc::new(String::from(name)))
+        self.lookup.get(name)
+    }
+
+    /// The name as written in the game, with any instance number suffix.
+    pub fn full_name(&self, name: &Name) -> Cow<'_, str> {
+        match name.instance {
+            0 => Cow::Borrowed(&self.names[name.id]),
+            instance => Cow::Owned(format!("{}_{}", &self[name.id], instance - 1)),
+        }
+    }
+
+    pub(crate) fn get(&self, id: usize) -> &NameStr<'a> {
+        &self.names[id]
+    }
+}
+
+/// Unreal writes `Name_12` as `Name` with instance 13, a number without
+/// leading zeros after the last underscore.
+fn split_instance(name: &str) -> Option<(usize, u32)> {
+    let underscore = name.rfind('_')?;
+    let digits = &name[underscore + 1..];
+    if digits.is_empty()
+        || !digits.bytes().all(|b| b.is_ascii_digit())
+        || (digits.len() > 1 && digits.starts_with('0'))
+    {
+        return None;
     }
+    let number: u32 = digits.parse().ok()?;
+    Some((underscore, number.checked_add(1)?))
 }
 
-fn name_id(lookup: &HashMap<Rc<String>, usize>, name: &str) -> usize {
-    *lookup.get(&Rc::new(String::from(name))).unwrap_or(&0)
+fn name_id(lookup: &HashMap<NameStr, usize>, name: &str) -> usize {
+    *lookup.get(name).unwrap_or(&0)
 }
 
-impl Names {
+impl<'a> Names<'a> {
     pub fn len(&self) -> usize {
         self.names.len()
     }
 }
 
-impl Index<usize> for Names {
+impl<'a> Index<usize> for Names<'a> {
     type Output = str;
 
     fn index(&self, i: usize) -> &str {
-        self.names[i].as_str()
+        &self.names[i]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::{names, NAMES};
+    use crate::io::{ArrayReader, Writer};
+
+    #[test]
+    fn shared_names() {
+        let mut data = Vec::new();
+        data.write_i32(NAMES.len() as i32).unwrap();
+        for name in NAMES {
+            data.write_str(name).unwrap();
+        }
+        // Borrowed from the save
+        let borrowed = Names::new(&mut ArrayReader::new(&data), 0).unwrap();
+        let range = data.as_ptr_range();
+        for id in 1..borrowed.len() {
+            assert!(range.contains(&borrowed[id].as_ptr()));
+        }
+
+        // Copied once, shared by the table and its lookup
+        let mut owned = names();
+        let id = owned.intern(Cow::Owned(String::from("Added")));
+        for id in &[1, id] {
+            let (key, _) = owned.lookup.get_key_value(&owned[*id]).unwrap();
+            assert!(std::ptr::eq(key.as_ptr(), owned[*id].as_ptr()));
+        }
+        assert_eq!(owned.get_name_id("Added"), Some(&id));
+    }
+
+    #[test]
+    fn instances() {
+        assert_eq!(split_instance("Dodo_Character_BP_C_12"), Some((19, 13)));
+        assert_eq!(split_instance("Dodo_Character_BP_C_0"), Some((19, 1)));
+        assert_eq!(split_instance("Dodo_Character_BP_C_012"), None);
+        assert_eq!(split_instance("Dodo_Character_BP_C"), None);
+        assert_eq!(split_instance("Dodo_"), None);
     }
 }
@@ -1,32 +1,87 @@
 use super::{Location, Names};
+use crate::file::HibernationEntry;
 use crate::io::Name;
-use crate::properties::{Properties, Value};
+use crate::properties::{Properties, Property, Value};
+use crate::{ArkError, Result};
 use enumset::EnumSetType;
 use lazy_static::lazy_static;
 use regex::Regex;
 use serde::Serialize;
 use std::rc::Rc;
 
-pub struct Object {
+#[derive(Clone)]
+pub struct Object<'a> {
     pub guid: u128,
     pub name: Name,
     pub is_item: bool,
+    /// Additional class names, listed after the object's own name.
+    pub extra_names: Vec<Name>,
+    /// Raw boolean, set for objects that belong to a streamed sub-level.
+    pub from_data_file: i32,
+    pub data_file_index: i32,
     pub location: Option<Location>,
-    pub properties: Properties,
+    pub properties: Properties<'a>,
+    /// Raw value stored after the properties offset, usually zero.
+    pub unknown: i32,
     pub object_type: Type,
     pub status_component: Option<usize>,
     pub inventory_component: Option<usize>,
-    pub(super) names: Rc<Names>,
+    /// The hibernation cell this object was stored in, if any.
+    pub hibernation: Option<Rc<HibernationEntry>>,
+    /// Index of the cryopod item this creature was stored in, if any.
+    pub cryopod: Option<usize>,
+    pub(super) names: Rc<Names<'a>>,
 }
 
-impl Object {
+/// An object as read from the save, held until the names table is complete.
+pub(crate) struct ObjectRecord<'a> {
+    pub guid: u128,
+    pub name: Name,
+    pub is_item: bool,
+    pub extra_names: Vec<Name>,
+    pub from_data_file: i32,
+    pub data_file_index: i32,
+    pub location: Option<Location>,
+    pub properties: Properties<'a>,
+    pub unknown: i32,
+    pub hibernation: Option<Rc<HibernationEntry>>,
+    pub cryopod: Option<usize>,
+    /// Added to component indexes, which are relative to the section the
+    /// object was read from.
+    pub first_index: usize,
+}
+
+impl<'a> ObjectRecord<'a> {
+    pub fn into_object(self, names: &Rc<Names<'a>>) -> Object<'a> {
+        let first_index = self.first_index;
+        let mut object = Object::new(
+            self.guid,
+            self.name,
+            self.is_item,
+            self.location,
+            self.properties,
+            names.clone(),
+        );
+        object.status_component = object.status_component.map(|i| i + first_index);
+        object.inventory_component = object.inventory_component.map(|i| i + first_index);
+        object.extra_names = self.extra_names;
+        object.from_data_file = self.from_data_file;
+        object.data_file_index = self.data_file_index;
+        object.unknown = self.unknown;
+        object.hibernation = self.hibernation;
+        object.cryopod = self.cryopod;
+        object
+    }
+}
+
+impl<'a> Object<'a> {
     pub fn new(
         guid: u128,
         name: Name,
         is_item: bool,
         location: Option<Location>,
-        properties: Properties,
-        names: Rc<Names>,
+        properties: Properties<'a>,
+        names: Rc<Names<'a>>,
     ) -> Self {
         lazy_static! {
             static ref EGG_RE: Regex = Regex::new(r"Egg.*Fertilized").unwrap();
@@ -34,7 +89,7 @@ impl Object {
         let class = &names[name.id];
         let object_type = match 1 {
             _ if is_item => {
-                if EGG_RE.is_match(&class) {
+                if EGG_RE.is_match(class) {
                     Type::FertilizedEgg
                 } else {
                     Type::Item
@@ -86,39 +141,105 @@ impl Object {
             _ => Type::Unknown,
         };
 
-        let status_name = names.get_name_id("MyCharacterStatusComponent").unwrap();
-        let inventory_name = names.get_name_id("MyInventoryComponent").unwrap();
-
-        let status_component = match properties.props.get(status_name) {
-            Some(index) => match index[0].value {
-                Value::Int(ind) => Some(ind as usize),
-                _ => None,
-            },
-            None => None,
-        };
-
-        let inventory_component = match properties.props.get(inventory_name) {
-            Some(index) => match index[0].value {
-                Value::Int(ind) => Some(ind as usize),
-                _ => None,
-            },
-            None => None,
-        };
+        let status_component = component_index(&names, &properties, "MyCharacterStatusComponent");
+        let inventory_component = component_index(&names, &properties, "MyInventoryComponent");
 
         Object {
             guid,
             name,
             is_item,
+            extra_names: Vec::new(),
+            from_data_file: 0,
+            data_file_index: 0,
             location,
             properties,
+            unknown: 0,
             object_type,
             names,
             status_component,
             inventory_component,
+            hibernation: None,
+            cryopod: None,
         }
     }
 }
 
+impl<'a> Object<'a> {
+    /// Replaces the value of a property, which must hold the same kind of
+    /// value as before, such as `TamedName` to rename a creature.
+    pub fn set_property(&mut self, name: &str, value: Value<'a>) -> Result<()> {
+        self.set_property_index(name, 0, value)
+    }
+
+    /// Replaces the value at one index of a static array property, such as
+    /// a single stat of `NumberOfLevelUpPointsApplied`.
+    pub fn set_property_index(&mut self, name: &str, ind: u32, value: Value<'a>) -> Result<()> {
+        let id = self.name_id(name)?;
+        self.properties
+            .set_index(&id, ind, value)
+            .map_err(|e| e.in_property(name))
+    }
+
+    /// Adds a property, replacing any value it already has. `inner_type` is
+    /// the struct type of a `StructProperty`, the element type of an
+    /// `ArrayProperty` or the enum type of a `ByteProperty` holding an enum.
+    pub fn insert_property(
+        &mut self,
+        name: &str,
+        type_name: &str,
+        inner_type: Option<&str>,
+        value: Value<'a>,
+    ) -> Result<()> {
+        let id = self.name_id(name)?;
+        let type_name = Name {
+            id: self.name_id(type_name)?,
+            instance: 0,
+        };
+        let inner_type = match inner_type {
+            Some(inner_type) => Some(Name {
+                id: self.name_id(inner_type)?,
+                instance: 0,
+            }),
+            None => None,
+        };
+        let property = Property::new(Name { id, instance: 0 }, type_name, inner_type, value);
+        property
+            .check(&self.names)
+            .map_err(|e| e.in_property(name))?;
+        match self.properties.props.get_mut(&id) {
+            Some(properties) => {
+                let name = properties[0].name;
+                *properties = vec![Property { name, ..property }];
+            }
+            None => self.properties.insert(property, self.names.get(id).clone()),
+        }
+        Ok(())
+    }
+
+    /// Removes every index of a property, returning them.
+    pub fn remove_property(&mut self, name: &str) -> Option<Vec<Property<'a>>> {
+        let id = *self.names.get_name_id(name)?;
+        self.properties.remove(&id, name)
+    }
+
+    fn name_id(&self, name: &str) -> Result<usize> {
+        match self.names.get_name_id(name) {
+            Some(id) => Ok(*id),
+            None => Err(ArkError::UnknownName {
+                name: String::from(name),
+            }),
+        }
+    }
+}
+
+fn component_index(names: &Names, properties: &Properties, name: &str) -> Option<usize> {
+    let id = names.get_name_id(name)?;
+    match properties.props.get(id)?[0].value {
+        Value::Int(ind) => Some(ind as usize),
+        _ => None,
+    }
+}
+
 #[derive(Debug, EnumSetType, Serialize)]
 pub enum Type {
     DeathItemCache,
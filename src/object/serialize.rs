@@ -3,7 +3,7 @@ use crate::properties::{Properties, Property, Value};
 use serde::ser::{Serialize, SerializeMap, SerializeSeq, SerializeTuple, Serializer};
 use std::rc::Rc;
 
-impl Serialize for Entry {
+impl<'a> Serialize for Entry<'a> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
@@ -12,7 +12,7 @@ impl Serialize for Entry {
     }
 }
 
-impl Serialize for Object {
+impl<'a> Serialize for Object<'a> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
@@ -26,6 +26,9 @@ impl Serialize for Object {
         if let Some(inventory) = self.inventory_component {
             map.serialize_entry("InventoryComponent", &inventory)?;
         }
+        if let Some(cryopod) = self.cryopod {
+            map.serialize_entry("Cryopod", &cryopod)?;
+        }
         for p in self.properties.props.values() {
             map.serialize_key(&self.names[p[0].name.id])?;
             if p.len() == 1 {
@@ -44,11 +47,11 @@ impl Serialize for Object {
     }
 }
 
-struct ValueVec<'a> {
-    names: &'a Rc<Names>,
-    values: &'a Vec<Property>,
+struct ValueVec<'s, 'a> {
+    names: &'s Rc<Names<'a>>,
+    values: &'s Vec<Property<'a>>,
 }
-impl<'a> Serialize for ValueVec<'a> {
+impl<'s, 'a> Serialize for ValueVec<'s, 'a> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
@@ -61,7 +64,7 @@ impl<'a> Serialize for ValueVec<'a> {
                 i += 1;
             }
             list.serialize_element(&NameValue {
-                names: &self.names,
+                names: self.names,
                 value: &v.value,
             })?;
             i += 1;
@@ -70,14 +73,14 @@ impl<'a> Serialize for ValueVec<'a> {
     }
 }
 
-struct NameValue<'a> {
-    names: &'a Rc<Names>,
-    value: &'a Value,
+struct NameValue<'s, 'a> {
+    names: &'s Rc<Names<'a>>,
+    value: &'s Value<'a>,
 }
 
-struct NameProperty<'a> {
-    names: &'a Rc<Names>,
-    property: &'a Vec<Property>,
+struct NameProperty<'s, 'a> {
+    names: &'s Rc<Names<'a>>,
+    property: &'s Vec<Property<'a>>,
 }
 
 macro_rules! serialize {
@@ -90,7 +93,7 @@ macro_rules! serialize {
     }};
 }
 
-impl<'a> Serialize for NameValue<'a> {
+impl<'s, 'a> Serialize for NameValue<'s, 'a> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
@@ -101,9 +104,23 @@ impl<'a> Serialize for NameValue<'a> {
             Value::ArrayOfI16(v) => serialize!(serializer, v),
             Value::ArrayOfI32(v) => serialize!(serializer, v),
             Value::ArrayOfI8(v) => serialize!(serializer, v),
-            Value::ArrayOfName(v) => serialize!(serializer, v),
-            Value::ArrayOfObject(v) => serialize!(serializer, v),
-            Value::ArrayOfStruct(v) => serialize!(serializer, v),
+            Value::ArrayOfName(v) => {
+                let mut seq = serializer.serialize_seq(Some(v.len()))?;
+                for name in v {
+                    seq.serialize_element(&self.names.full_name(name))?;
+                }
+                seq.end()
+            }
+            Value::ArrayOfObject(v) | Value::ArrayOfStruct(v) => {
+                let mut seq = serializer.serialize_seq(Some(v.len()))?;
+                for value in v {
+                    seq.serialize_element(&NameValue {
+                        names: self.names,
+                        value,
+                    })?;
+                }
+                seq.end()
+            }
             Value::ArrayOfStr(v) => serialize!(serializer, v),
             Value::ArrayOfU16(v) => serialize!(serializer, v),
             Value::ArrayOfU32(v) => serialize!(serializer, v),
@@ -111,13 +128,56 @@ impl<'a> Serialize for NameValue<'a> {
             Value::ArrayOfU8(v) => serialize!(serializer, v),
             Value::ArrayOfBool(v) => serialize!(serializer, v),
             Value::Bool(v) => serializer.serialize_bool(*v),
+            Value::Box { min, max, valid } => {
+                let mut map = serializer.serialize_map(Some(3))?;
+                map.serialize_entry("Min", min)?;
+                map.serialize_entry("Max", max)?;
+                map.serialize_entry("IsValid", valid)?;
+                map.end()
+            }
             Value::Byte(v) => serializer.serialize_bytes(&[*v]),
             Value::Double(v) => serializer.serialize_f64(*v),
-            Value::Enum(v, _) => serializer.serialize_str(&self.names[v.id]),
+            Value::Enum(_, v) => serializer.serialize_str(&self.names.full_name(v)),
             Value::Float(v) => serializer.serialize_f32(*v),
+            Value::Guid(_) => serializer.serialize_str(&self.value.to_string()),
             Value::Int16(v) => serializer.serialize_i16(*v),
             Value::Int8(v) => serializer.serialize_i8(*v),
             Value::Int(v) => serializer.serialize_i32(*v),
+            Value::IntPoint(x, y) => {
+                let mut tup = serializer.serialize_tuple(2)?;
+                tup.serialize_element(&x)?;
+                tup.serialize_element(&y)?;
+                tup.end()
+            }
+            Value::IntVector(x, y, z) => {
+                let mut tup = serializer.serialize_tuple(3)?;
+                tup.serialize_element(&x)?;
+                tup.serialize_element(&y)?;
+                tup.serialize_element(&z)?;
+                tup.end()
+            }
+            Value::ItemNetId(id1, id2) => {
+                let mut tup = serializer.serialize_tuple(2)?;
+                tup.serialize_element(&id1)?;
+                tup.serialize_element(&id2)?;
+                tup.end()
+            }
+            Value::Map(v) => {
+                let mut seq = serializer.serialize_seq(Some(v.len()))?;
+                for (key, value) in v {
+                    seq.serialize_element(&(
+                        NameValue {
+                            names: self.names,
+                            value: key,
+                        },
+                        NameValue {
+                            names: self.names,
+                            value,
+                        },
+                    ))?;
+                }
+                seq.end()
+            }
             Value::Name(v) => serializer.serialize_str(&self.names[v.id]),
             Value::Properties(v) => {
                 let mut map = serializer.serialize_map(Some(v.props.len()))?;
@@ -148,10 +208,46 @@ impl<'a> Serialize for NameValue<'a> {
                 tup.serialize_element(&a)?;
                 tup.end()
             }
-            Value::String(v) => serializer.serialize_str(v.as_str()),
+            Value::Rotator(pitch, yaw, roll) => {
+                let mut tup = serializer.serialize_tuple(3)?;
+                tup.serialize_element(&pitch)?;
+                tup.serialize_element(&yaw)?;
+                tup.serialize_element(&roll)?;
+                tup.end()
+            }
+            Value::Set(v) => {
+                let mut seq = serializer.serialize_seq(Some(v.len()))?;
+                for value in v {
+                    seq.serialize_element(&NameValue {
+                        names: self.names,
+                        value,
+                    })?;
+                }
+                seq.end()
+            }
+            Value::String(v) => serializer.serialize_str(v),
+            Value::Text(v) => serializer.serialize_str(&v.to_string()),
+            Value::Transform {
+                rotation,
+                translation,
+                scale,
+            } => {
+                let mut map = serializer.serialize_map(Some(3))?;
+                map.serialize_entry("Rotation", rotation)?;
+                map.serialize_entry("Translation", translation)?;
+                map.serialize_entry("Scale3D", scale)?;
+                map.end()
+            }
             Value::UInt16(v) => serializer.serialize_u16(*v),
             Value::UInt32(v) => serializer.serialize_u32(*v),
             Value::UInt64(v) => serializer.serialize_u64(*v),
+            Value::UniqueNetId { .. } => serializer.serialize_str(&self.value.to_string()),
+            Value::Unknown { type_name, raw } => {
+                let mut map = serializer.serialize_map(Some(2))?;
+                map.serialize_entry("Type", &self.names.full_name(type_name))?;
+                map.serialize_entry("Data", &base64::encode(raw))?;
+                map.end()
+            }
             Value::Vector(x, y, z) => {
                 let mut tup = serializer.serialize_tuple(3)?;
                 tup.serialize_element(&x)?;
@@ -169,7 +265,7 @@ impl<'a> Serialize for NameValue<'a> {
     }
 }
 
-impl<'a> Serialize for NameProperty<'a> {
+impl<'s, 'a> Serialize for NameProperty<'s, 'a> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
@@ -193,7 +289,7 @@ impl<'a> Serialize for NameProperty<'a> {
     }
 }
 
-impl Serialize for Properties {
+impl<'a> Serialize for Properties<'a> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
@@ -202,3 +298,26 @@ impl Serialize for Properties {
         map.end()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::fixture::save;
+    use crate::ArkParser;
+
+    #[test]
+    fn enum_arrays() {
+        let data = save();
+        let parser = ArkParser::read_borrowed(&data).unwrap();
+        let json = serde_json::to_string(&parser.entries()[0]).unwrap();
+        assert!(json.contains(r#""Moods":["EDinoMood_0","EDinoMood_1"]"#));
+    }
+
+    #[test]
+    fn struct_arrays() {
+        let data = save();
+        let parser = ArkParser::read_borrowed(&data).unwrap();
+        let json = serde_json::to_string(&parser.entries()[0]).unwrap();
+        assert!(json.contains(r#""Ancestors":[{"TargetingTeam":1},{"TargetingTeam":2}]"#));
+        assert!(json.contains(r#""Colors":[[1.0,0.0,0.0,1.0],[0.0,0.0,1.0,1.0]]"#));
+    }
+}
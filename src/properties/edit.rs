@@ -0,0 +1,157 @@
+use crate::io::Name;
+use crate::object::{NameStr, Names};
+use crate::properties::{Properties, Property, Value};
+use crate::{ArkError, Result};
+use std::collections::hash_map::Entry;
+use std::mem::discriminant;
+
+impl<'a> Properties<'a> {
+    /// The value of a property for changing in place.
+    pub fn get_mut(&mut self, name: &usize) -> Option<&mut Value<'a>> {
+        Some(&mut self.props.get_mut(name)?.first_mut()?.value)
+    }
+
+    /// Replaces the value of a property, which must hold the same kind of
+    /// value as before.
+    pub fn set(&mut self, name: &usize, value: Value<'a>) -> Result<()> {
+        self.set_index(name, 0, value)
+    }
+
+    /// Replaces the value at one index of a static array property, adding
+    /// the index if the property only has others.
+    pub fn set_index(&mut self, name: &usize, ind: u32, value: Value<'a>) -> Result<()> {
+        let properties = self
+            .props
+            .get_mut(name)
+            .ok_or(ArkError::MissingProperty { ind })?;
+        let at = properties.iter().position(|p| p.ind >= ind);
+        let existing = &properties[at.unwrap_or(0)];
+        if discriminant(&existing.value) != discriminant(&value) {
+            return Err(ArkError::TypeMismatch {
+                type_name: String::from(existing.value.kind()),
+                value: value.to_string(),
+            });
+        }
+        match at {
+            Some(at) if properties[at].ind == ind => {
+                properties[at].value = value;
+            }
+            _ => {
+                let property = Property {
+                    ind,
+                    value,
+                    ..existing.clone()
+                };
+                properties.insert(at.unwrap_or(properties.len()), property);
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds a property, replacing any with the same name and index.
+    pub(crate) fn insert(&mut self, property: Property<'a>, name: NameStr<'a>) {
+        let id = property.name.id;
+        match self.props.entry(id) {
+            Entry::Occupied(mut e) => {
+                let properties = e.get_mut();
+                properties.retain(|p| p.ind != property.ind);
+                let at = properties
+                    .iter()
+                    .position(|p| p.ind > property.ind)
+                    .unwrap_or(properties.len());
+                properties.insert(at, property);
+            }
+            Entry::Vacant(e) => {
+                e.insert(vec![property]);
+                self.set.insert(name);
+                self.order.push(id);
+            }
+        }
+    }
+
+    /// Removes every index of a property, returning them.
+    pub(crate) fn remove(&mut self, name: &usize, name_str: &str) -> Option<Vec<Property<'a>>> {
+        let removed = self.props.remove(name)?;
+        self.order.retain(|id| id != name);
+        self.set.remove(name_str);
+        Some(removed)
+    }
+
+    /// Replaces every object index referred to by an `ObjectProperty`.
+    pub(crate) fn remap_objects(&mut self, names: &Names, map: &dyn Fn(i32) -> i32) {
+        for property in self.props.values_mut().flatten() {
+            property.remap_objects(names, map);
+        }
+    }
+}
+
+impl<'a> Property<'a> {
+    fn remap_objects(&mut self, names: &Names, map: &dyn Fn(i32) -> i32) {
+        let type_id = self.type_name.id;
+        match &mut self.value {
+            Value::Int(index) if type_id == names.object_property => *index = map(*index),
+            Value::ArrayOfObject(items) => {
+                for item in items {
+                    if let Value::Int(index) = item {
+                        *index = map(*index);
+                    }
+                }
+            }
+            Value::Properties(properties) if type_id == names.struct_property => {
+                properties.remap_objects(names, map)
+            }
+            Value::ArrayOfStruct(items) => {
+                for item in items {
+                    if let Value::Properties(properties) = item {
+                        properties.remap_objects(names, map);
+                    }
+                }
+            }
+            Value::Map(entries) => {
+                let keys = is_object(self.inner_type, names);
+                let values = is_object(self.value_type, names);
+                for (key, value) in entries {
+                    remap_element(key, keys, names, map);
+                    remap_element(value, values, names, map);
+                }
+            }
+            Value::Set(items) => {
+                let objects = is_object(self.inner_type, names);
+                for item in items {
+                    remap_element(item, objects, names, map);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// A new property for inserting, with index 0.
+    pub(crate) fn new(
+        name: Name,
+        type_name: Name,
+        inner_type: Option<Name>,
+        value: Value<'a>,
+    ) -> Self {
+        Property {
+            name,
+            type_name,
+            inner_type,
+            value_type: None,
+            ind: 0,
+            value,
+        }
+    }
+}
+
+fn is_object(type_name: Option<Name>, names: &Names) -> bool {
+    type_name.map(|t| t.id) == Some(names.object_property)
+}
+
+/// Remaps a key, value or element of a map or set.
+fn remap_element(value: &mut Value, is_object: bool, names: &Names, map: &dyn Fn(i32) -> i32) {
+    match value {
+        Value::Int(index) if is_object => *index = map(*index),
+        Value::Properties(properties) => properties.remap_objects(names, map),
+        _ => {}
+    }
+}
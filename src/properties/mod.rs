@@ -1,28 +1,49 @@
 use crate::io::Name;
+use crate::object::NameStr;
 use std::collections::hash_map::HashMap;
 use std::collections::hash_set::HashSet;
+mod edit;
 mod read;
+mod text;
 mod value;
+mod write;
+pub use text::{FormatArgument, Text, TextHistory};
 pub use value::Value;
+pub(crate) use write::NameWriter;
 
-#[derive(Debug)]
-pub struct Property {
+#[derive(Debug, Clone)]
+pub struct Property<'a> {
     pub name: Name,
+    /// The property type, such as `IntProperty`.
+    pub type_name: Name,
+    /// The struct type of a `StructProperty`, the element type of an
+    /// `ArrayProperty` or `SetProperty`, the key type of a `MapProperty` or
+    /// the enum type of a `ByteProperty`.
+    pub inner_type: Option<Name>,
+    /// The value type of a `MapProperty` or the struct type of an
+    /// `ArrayProperty` of structs, if it has a native layout.
+    pub value_type: Option<Name>,
     pub ind: u32,
-    pub value: Value,
+    /// The decoded value, which is written back as the same bytes it was
+    /// read from.
+    pub value: Value<'a>,
 }
 
-#[derive(Debug)]
-pub struct Properties {
-    pub props: HashMap<usize, Vec<Property>>,
-    set: HashSet<String>,
+#[derive(Debug, Clone)]
+pub struct Properties<'a> {
+    pub props: HashMap<usize, Vec<Property<'a>>>,
+    set: HashSet<NameStr<'a>>,
+    /// Property names in the order they were read, which is the order they
+    /// are written in.
+    order: Vec<usize>,
 }
 
-impl<'a> Properties {
+impl<'a> Properties<'a> {
     pub fn new() -> Self {
         Properties {
             props: HashMap::new(),
             set: HashSet::new(),
+            order: Vec::new(),
         }
     }
 
@@ -36,7 +57,7 @@ impl<'a> Properties {
 
     pub fn get_bool(&self, name: &usize) -> Option<bool> {
         match self.props.get(name) {
-            Some(value) => match value.get(0) {
+            Some(value) => match value.first() {
                 Some(prop) => match prop.value {
                     Value::Bool(value) => Some(value),
                     _ => None,
@@ -49,7 +70,7 @@ impl<'a> Properties {
 
     pub fn get_i32(&self, name: &usize) -> Option<i32> {
         match self.props.get(name) {
-            Some(value) => match value.get(0) {
+            Some(value) => match value.first() {
                 Some(prop) => match prop.value {
                     Value::Byte(v) => Some(v as i32),
                     Value::Double(v) => Some(v as i32),
@@ -68,6 +89,48 @@ impl<'a> Properties {
         }
     }
 
+    pub fn get_f32(&self, name: &usize) -> Option<f32> {
+        match &self.props.get(name)?.first()?.value {
+            Value::Double(v) => Some(*v as f32),
+            Value::Float(v) => Some(*v),
+            _ => self.get_i32(name).map(|v| v as f32),
+        }
+    }
+
+    pub fn get_u64(&self, name: &usize) -> Option<u64> {
+        match &self.props.get(name)?.first()?.value {
+            Value::UInt64(v) => Some(*v),
+            _ => self.get_i32(name).map(|v| v as u64),
+        }
+    }
+
+    pub fn get(&self, name: &usize) -> Option<&Value<'a>> {
+        Some(&self.props.get(name)?.first()?.value)
+    }
+
+    /// The properties of each struct in a struct or array of structs.
+    pub fn get_structs(&self, name: &usize) -> Vec<&Properties<'a>> {
+        match self.get(name) {
+            Some(Value::Properties(item)) => vec![item],
+            Some(Value::ArrayOfStruct(items)) => items
+                .iter()
+                .filter_map(|item| match item {
+                    Value::Properties(item) => Some(item),
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// The properties of a struct value.
+    pub fn get_properties(&self, name: &usize) -> Option<&Properties<'a>> {
+        match &self.props.get(name)?.first()?.value {
+            Value::Properties(properties) => Some(properties),
+            _ => None,
+        }
+    }
+
     pub fn get_vec_int(&self, name: &usize) -> Vec<i32> {
         match self.props.get(name) {
             Some(values) => {
@@ -100,7 +163,7 @@ impl<'a> Properties {
 
     pub fn get_str(&self, name: &usize) -> Option<&str> {
         match self.props.get(name) {
-            Some(value) => match value.get(0) {
+            Some(value) => match value.first() {
                 Some(prop) => match &prop.value {
                     Value::String(value) => Some(value),
                     _ => None,
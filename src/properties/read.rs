@@ -1,44 +1,29 @@
-use crate::io::{Name, Reader};
+use crate::io::{fstring, Name, Reader, Source};
 use crate::object::Names;
-use crate::properties::{Properties, Property, Value};
-use base64::decode;
+use crate::properties::{FormatArgument, Properties, Property, Text, TextHistory, Value};
+use crate::{ArkError, Result};
+use std::borrow::Cow;
 use std::collections::hash_map::Entry;
-use std::io::{Result, SeekFrom};
-use std::rc::Rc;
+use std::io::SeekFrom;
 
-impl<'a> Properties {
-    pub fn read(&mut self, file: &mut dyn Reader, names: &Rc<Names>) -> Result<()> {
+impl<'a> Properties<'a> {
+    pub fn read(&mut self, file: &mut dyn Source<'a>, names: &mut Names<'a>) -> Result<()> {
         loop {
-            let id = file.read_u32()? as usize;
-            if &names[id] == "None" {
+            let name = names.read_name(file)?;
+            if &names[name.id] == "None" {
                 break;
             }
-            let instance = file.read_u32()?;
-            let type_id = file.read_u32()? as usize;
-            file.seek(SeekFrom::Current(4))?;
+            let id = name.id;
+            let type_name = names.read_name(file)?;
             let data_size = file.read_u32()? as usize;
             let ind = file.read_u32()?;
-            let value = match type_id {
-                _ if type_id == names.array_property => read_array(file, data_size, names)?,
-                _ if type_id == names.bool_property => Value::Bool(file.read_u8()? != 0),
-                _ if type_id == names.byte_property => read_byte(file, names)?,
-                _ if type_id == names.double_property => Value::Double(file.read_f64()?),
-                _ if type_id == names.float_property => Value::Float(file.read_f32()?),
-                _ if type_id == names.int16_property => Value::Int16(file.read_i16()?),
-                _ if type_id == names.int8_property => Value::Int8(file.read_i8()?),
-                _ if type_id == names.int_property => Value::Int(file.read_i32()?),
-                _ if type_id == names.name_property => Value::Name(file.read_name()?),
-                _ if type_id == names.object_property => read_object(file, data_size)?,
-                _ if type_id == names.str_property => Value::String(file.read_str()?),
-                _ if type_id == names.struct_property => read_struct(file, data_size, names)?,
-                _ if type_id == names.text_property => read_text(file, data_size)?,
-                _ if type_id == names.uint16_property => Value::UInt16(file.read_u16()?),
-                _ if type_id == names.uint32_property => Value::UInt32(file.read_u32()?),
-                _ if type_id == names.uint64_property => Value::UInt64(file.read_u64()?),
-                _ => panic!("Unknown type {}", &names[type_id]),
-            };
+            let (tag, value) = read_value(file, name, type_name, data_size, names)
+                .map_err(|e| e.in_property(&names[id]))?;
             let property = Property {
-                name: Name { id, instance },
+                name,
+                type_name,
+                inner_type: tag.inner_type,
+                value_type: tag.value_type,
                 ind,
                 value,
             };
@@ -46,7 +31,8 @@ impl<'a> Properties {
                 Entry::Occupied(mut e) => e.get_mut().push(property),
                 Entry::Vacant(e) => {
                     e.insert(vec![property]);
-                    self.set.insert(names[id].into());
+                    self.set.insert(names.get(id).clone());
+                    self.order.push(id);
                 }
             }
         }
@@ -54,132 +40,464 @@ impl<'a> Properties {
     }
 }
 
+/// The types named in a tag before the value, and the struct type of an array
+/// of structs, kept with the property so it can be written back.
+#[derive(Default)]
+struct TagTypes {
+    inner_type: Option<Name>,
+    value_type: Option<Name>,
+}
+
+/// Reads a property's tag and value. A value that can't be decoded, or that
+/// wouldn't be written back as the same bytes, is kept as raw bytes instead.
+fn read_value<'a>(
+    file: &mut dyn Source<'a>,
+    name: Name,
+    type_name: Name,
+    data_size: usize,
+    names: &mut Names<'a>,
+) -> Result<(TagTypes, Value<'a>)> {
+    let type_id = type_name.id;
+    let mut tag = TagTypes::default();
+    if type_id == names.bool_property {
+        // The value of a bool is part of its tag
+        let value = read_bool(file)?;
+        skip_property_guid(file, names)?;
+        if data_size != 0 {
+            return Err(ArkError::InvalidData {
+                offset: file.stream_position()?,
+                message: format!("Bool property with data size {}", data_size),
+            });
+        }
+        return Ok((tag, Value::Bool(value)));
+    }
+    if type_id == names.array_property
+        || type_id == names.byte_property
+        || type_id == names.map_property
+        || type_id == names.set_property
+        || type_id == names.struct_property
+    {
+        tag.inner_type = Some(names.read_name(file)?);
+    }
+    if type_id == names.map_property {
+        tag.value_type = Some(names.read_name(file)?);
+    }
+    if type_id == names.struct_property && names.asa {
+        file.read_u128()?;
+    }
+    skip_property_guid(file, names)?;
+
+    let start = file.stream_position()?;
+    let end = start + data_size as u64;
+    let inner_type = tag.inner_type.unwrap_or(type_name);
+    let value = match type_id {
+        _ if type_id == names.array_property => {
+            read_array(file, name, inner_type, data_size, names).map(|(struct_type, value)| {
+                tag.value_type = struct_type;
+                value
+            })
+        }
+        _ if type_id == names.byte_property => read_byte(file, inner_type, names),
+        _ if type_id == names.map_property => {
+            read_map(file, inner_type.id, tag.value_type.unwrap().id, names)
+        }
+        _ if type_id == names.object_property => read_object(file, data_size, names),
+        _ if type_id == names.set_property => read_set(file, inner_type.id, names),
+        _ if type_id == names.struct_property => read_struct(file, inner_type, data_size, names),
+        _ if type_id == names.text_property => read_text_value(file, names).map(Value::Text),
+        _ => read_element(file, type_id, names),
+    };
+    match value {
+        Ok(value) if file.stream_position()? == end => Ok((tag, value)),
+        // A value cut short by the end of the file can't be kept either
+        Err(e) if matches!(e.root(), ArkError::Io(_) | ArkError::UnexpectedEof { .. }) => Err(e),
+        _ => {
+            file.seek(SeekFrom::Start(start))?;
+            // Arrays are written by their element type
+            let unknown_type = if type_id == names.array_property {
+                inner_type
+            } else {
+                type_name
+            };
+            Ok((tag, read_unknown(file, unknown_type, data_size)?))
+        }
+    }
+}
+
+/// Keeps the value of a type that can't be decoded, such as those added by
+/// mods, so it can be written back.
+fn read_unknown<'a>(file: &mut dyn Reader, type_name: Name, data_size: usize) -> Result<Value<'a>> {
+    Ok(Value::Unknown {
+        type_name,
+        raw: file.read_bytes(data_size)?,
+    })
+}
+
+/// Unreal Engine 5 tags end with a flag for an optional property guid.
+fn skip_property_guid(file: &mut dyn Reader, names: &Names) -> Result<()> {
+    if names.asa && file.read_u8()? != 0 {
+        file.read_u128()?;
+    }
+    Ok(())
+}
+
+/// A string that is written back as the same bytes. Strings that aren't
+/// encoded the way the game encodes them, such as single byte strings that
+/// aren't ASCII, are errors so the value is kept as raw bytes.
+fn read_string<'a>(file: &mut dyn Source<'a>) -> Result<Cow<'a, str>> {
+    let size = file.read_i32()?;
+    let data = file.read_data(fstring::byte_len(size))?;
+    let len = data.len() as u64;
+    let message = match fstring::decode(size, data) {
+        Ok(string) if fstring::encoded_size(&string) == size => return Ok(string),
+        Ok(string) => format!("{:?} isn't saved the way it would be written", string),
+        Err(message) => message,
+    };
+    Err(ArkError::InvalidString {
+        offset: file.stream_position()? - len - 4,
+        message,
+    })
+}
+
+/// A bool saved as a byte, which is an error unless it's 0 or 1.
+fn read_bool(file: &mut dyn Reader) -> Result<bool> {
+    match file.read_u8()? {
+        0 => Ok(false),
+        1 => Ok(true),
+        v => Err(ArkError::InvalidData {
+            offset: file.stream_position()? - 1,
+            message: format!("Bool saved as {}", v),
+        }),
+    }
+}
+
 macro_rules! arr {
-    ($file:ident, $method:ident, $arm:path) => {{
-        let size = $file.read_i32()? as usize;
-        let mut items = Vec::with_capacity(size);
-        for _ in 0..size {
-            items.push($file.$method()?);
+    ($file:ident, $size:ident, $read:expr, $arm:path) => {{
+        let count = $file.read_i32()?;
+        let mut items = Vec::with_capacity((count.max(0) as usize).min($size));
+        for _ in 0..count {
+            items.push($read);
         }
-        Ok($arm(items))
+        $arm(items)
     }};
 }
 
-fn read_array(file: &mut dyn Reader, size: usize, names: &Rc<Names>) -> Result<Value> {
-    let array_type = file.read_name()?;
+/// Reads the `name` array of `array_type` elements, with the struct type of
+/// an array of structs with a native layout.
+fn read_array<'a>(
+    file: &mut dyn Source<'a>,
+    name: Name,
+    array_type: Name,
+    size: usize,
+    names: &mut Names<'a>,
+) -> Result<(Option<Name>, Value<'a>)> {
     let id = array_type.id;
-    match id {
-        _ if id == names.object_property => {
-            let size = file.read_i32()? as usize;
-            let mut items = Vec::with_capacity(size);
-            for _ in 0..size {
-                items.push(match file.read_i32()? {
-                    0 => Value::Int(file.read_i32()?),
-                    1 => Value::Name(file.read_name()?),
-                    t @ _ => panic!("Unsupported object type {}", t),
-                });
+    let value = match id {
+        _ if id == names.object_property => arr!(
+            file,
+            size,
+            read_object_reference(file, names)?,
+            Value::ArrayOfObject
+        ),
+        _ if id == names.struct_property => {
+            let (count, struct_type) = read_struct_array_header(file, name, size, names)?;
+            // Structs without a native layout are each a list of properties
+            let struct_type_id = struct_type.map_or(names.struct_property, |t| t.id);
+            let mut items = Vec::with_capacity(count.min(size));
+            for _ in 0..count {
+                items.push(read_struct_value(file, struct_type_id, names)?);
             }
-            Ok(Value::ArrayOfObject(items))
+            return Ok((struct_type, Value::ArrayOfStruct(items)));
         }
-        _ if id == names.struct_property => {
-            let end_properties = file.seek(SeekFrom::Current(0))? + size as u64;
-            let count = file.read_i32()? as usize;
-            let mut items = Vec::with_capacity(count);
-            let struct_size = if count == 0 {
-                0
+        _ if id == names.uint32_property => arr!(file, size, file.read_u32()?, Value::ArrayOfU32),
+        _ if id == names.int_property => arr!(file, size, file.read_i32()?, Value::ArrayOfI32),
+        _ if id == names.uint16_property => arr!(file, size, file.read_u16()?, Value::ArrayOfU16),
+        _ if id == names.int16_property => arr!(file, size, file.read_i16()?, Value::ArrayOfI16),
+        _ if id == names.byte_property => {
+            let count = file.read_i32()?;
+            let capacity = (count.max(0) as usize).min(size);
+            // The enum type isn't saved for arrays, but enum values are names
+            if count > 0 && size == 4 + count as usize * 8 {
+                let mut items = Vec::with_capacity(capacity);
+                for _ in 0..count {
+                    items.push(names.read_name(file)?);
+                }
+                Value::ArrayOfName(items)
             } else {
-                (size - 4) / 4 / count
-            };
-            let struct_type_id = match struct_size {
-                1 => names.color_property,
-                3 => names.vector_property,
-                4 => names.linear_color_property,
-                _ => {
-                    let mut props = Properties::new();
-                    props.read(file, names)?;
-                    file.seek(SeekFrom::Start(end_properties))?;
-                    return Ok(Value::Properties(props));
+                let mut items = Vec::with_capacity(capacity);
+                for _ in 0..count {
+                    items.push(file.read_u8()?);
                 }
-            };
-            for _ in 0..count {
-                items.push(read_struct_value(file, struct_type_id, names)?);
+                Value::ArrayOfU8(items)
             }
-            file.seek(SeekFrom::Start(end_properties))?;
-            Ok(Value::ArrayOfStruct(items))
-        }
-        _ if id == names.uint32_property => arr!(file, read_u32, Value::ArrayOfU32),
-        _ if id == names.int_property => arr!(file, read_i32, Value::ArrayOfI32),
-        _ if id == names.uint16_property => arr!(file, read_u16, Value::ArrayOfU16),
-        _ if id == names.int16_property => arr!(file, read_i16, Value::ArrayOfI16),
-        _ if id == names.byte_property => arr!(file, read_u8, Value::ArrayOfU8),
-        _ if id == names.int8_property => arr!(file, read_i8, Value::ArrayOfI8),
-        _ if id == names.str_property => arr!(file, read_str, Value::ArrayOfStr),
-        _ if id == names.uint64_property => arr!(file, read_u64, Value::ArrayOfU64),
-        _ if id == names.bool_property => arr!(file, read_bool, Value::ArrayOfBool),
-        _ if id == names.float_property => arr!(file, read_f32, Value::ArrayOfF32),
-        _ if id == names.double_property => arr!(file, read_f64, Value::ArrayOfF64),
-        _ if id == names.name_property => arr!(file, read_f32, Value::ArrayOfF32),
-        // _ => panic!("Unknown array type {}", &names[array_type.id]),
+        }
+        _ if id == names.int8_property => arr!(file, size, file.read_i8()?, Value::ArrayOfI8),
+        _ if id == names.str_property => arr!(file, size, read_string(file)?, Value::ArrayOfStr),
+        _ if id == names.uint64_property => arr!(file, size, file.read_u64()?, Value::ArrayOfU64),
+        _ if id == names.bool_property => arr!(file, size, read_bool(file)?, Value::ArrayOfBool),
+        _ if id == names.float_property => arr!(file, size, file.read_f32()?, Value::ArrayOfF32),
+        _ if id == names.double_property => arr!(file, size, file.read_f64()?, Value::ArrayOfF64),
+        _ if id == names.name_property => {
+            arr!(file, size, names.read_name(file)?, Value::ArrayOfName)
+        }
+        _ => read_unknown(file, array_type, size)?,
+    };
+    Ok((None, value))
+}
+
+fn read_map<'a>(
+    file: &mut dyn Source<'a>,
+    key_type: usize,
+    value_type: usize,
+    names: &mut Names<'a>,
+) -> Result<Value<'a>> {
+    read_removed_keys(file)?;
+    let count = file.read_i32()?.max(0) as usize;
+    let mut entries = Vec::with_capacity(count.min(4096));
+    for _ in 0..count {
+        let key = read_element(file, key_type, names)?;
+        entries.push((key, read_element(file, value_type, names)?));
+    }
+    Ok(Value::Map(entries))
+}
+
+fn read_set<'a>(
+    file: &mut dyn Source<'a>,
+    element_type: usize,
+    names: &mut Names<'a>,
+) -> Result<Value<'a>> {
+    read_removed_keys(file)?;
+    let count = file.read_i32()?.max(0) as usize;
+    let mut items = Vec::with_capacity(count.min(4096));
+    for _ in 0..count {
+        items.push(read_element(file, element_type, names)?);
+    }
+    Ok(Value::Set(items))
+}
+
+/// Maps and sets start with the keys removed from the class defaults, which
+/// the game never saves.
+fn read_removed_keys(file: &mut dyn Reader) -> Result<()> {
+    let offset = file.stream_position()?;
+    match file.read_i32()? {
+        0 => Ok(()),
+        count => Err(ArkError::InvalidData {
+            offset,
+            message: format!("{} keys removed from defaults", count),
+        }),
+    }
+}
+
+/// A key, value or element of a map or set. Their struct type isn't saved,
+/// so structs are read as properties.
+fn read_element<'a>(
+    file: &mut dyn Source<'a>,
+    type_id: usize,
+    names: &mut Names<'a>,
+) -> Result<Value<'a>> {
+    Ok(match type_id {
+        _ if type_id == names.bool_property => Value::Bool(read_bool(file)?),
+        _ if type_id == names.byte_property => Value::Byte(file.read_u8()?),
+        _ if type_id == names.double_property => Value::Double(file.read_f64()?),
+        _ if type_id == names.float_property => Value::Float(file.read_f32()?),
+        _ if type_id == names.int16_property => Value::Int16(file.read_i16()?),
+        _ if type_id == names.int8_property => Value::Int8(file.read_i8()?),
+        _ if type_id == names.int_property => Value::Int(file.read_i32()?),
+        _ if type_id == names.name_property => Value::Name(names.read_name(file)?),
+        _ if type_id == names.object_property => read_object_reference(file, names)?,
+        _ if type_id == names.str_property => Value::String(read_string(file)?),
+        _ if type_id == names.struct_property => {
+            read_struct_value(file, names.struct_property, names)?
+        }
+        _ if type_id == names.uint16_property => Value::UInt16(file.read_u16()?),
+        _ if type_id == names.uint32_property => Value::UInt32(file.read_u32()?),
+        _ if type_id == names.uint64_property => Value::UInt64(file.read_u64()?),
         _ => {
-            file.seek(SeekFrom::Current(size as i64))?;
-            Ok(Value::ArrayOfI32(vec![]))
+            return Err(ArkError::UnknownPropertyType {
+                offset: file.stream_position()?,
+                type_name: String::from(&names[type_id]),
+            })
         }
+    })
+}
+
+/// Structs the game saves as bare fields rather than a list of properties,
+/// with their size in the original game.
+const NATIVE_STRUCTS: &[(&str, usize)] = &[
+    ("Color", 4),
+    ("Vector", 12),
+    ("LinearColor", 16),
+    ("Quat", 16),
+    ("Rotator", 12),
+    ("Vector2D", 8),
+    ("IntPoint", 8),
+    ("IntVector", 12),
+    ("Guid", 16),
+    ("Box", 25),
+    ("Transform", 40),
+];
+
+/// Arrays of structs in the original game whose struct type can't be told
+/// from their size, by property name.
+const STRUCT_ARRAYS: &[(&str, &str)] = &[
+    ("CustomColors", "Color"),
+    (
+        "CustomColours_60_7D3267C846B277953C0C41AEBD54FBCB",
+        "LinearColor",
+    ),
+];
+
+/// The element count and struct type of the `name` array of structs. Unreal
+/// Engine 5 saves a tag for the elements naming their type. The original
+/// game doesn't, so native structs are found by the property name or by
+/// size, leaving `None` for lists of properties.
+fn read_struct_array_header(
+    file: &mut dyn Reader,
+    name: Name,
+    size: usize,
+    names: &mut Names,
+) -> Result<(usize, Option<Name>)> {
+    let count = file.read_i32()?.max(0) as usize;
+    if names.asa {
+        names.read_name(file)?;
+        names.read_name(file)?;
+        file.read_u32()?;
+        file.read_u32()?;
+        let struct_type = names.read_name(file)?;
+        file.read_u128()?;
+        skip_property_guid(file, names)?;
+        return Ok((count, Some(struct_type)));
     }
+    let named = STRUCT_ARRAYS
+        .iter()
+        .find(|(array, _)| *array == &names[name.id])
+        .map(|(_, struct_type)| *struct_type);
+    let element_size = size.saturating_sub(4).checked_div(count);
+    // A list of properties is 8 bytes when empty and at least 33 otherwise,
+    // so only other sizes can be told apart from one. Sizes shared by several
+    // structs are read as the one the game saves most, a Vector for 12 bytes
+    // and a LinearColor for 16.
+    let mut sized = NATIVE_STRUCTS
+        .iter()
+        .filter(|(_, native_size)| Some(*native_size) == element_size && *native_size < 33);
+    let struct_type = named.or_else(|| match (sized.next(), sized.next()) {
+        (Some((struct_type, _)), None) => Some(*struct_type),
+        _ => match element_size {
+            Some(12) => Some("Vector"),
+            Some(16) => Some("LinearColor"),
+            _ => None,
+        },
+    });
+    Ok((
+        count,
+        struct_type.map(|struct_type| Name {
+            // Arrays don't name their struct type, so it may not be in the table
+            id: names.intern(Cow::Borrowed(struct_type)),
+            instance: 0,
+        }),
+    ))
 }
 
-fn read_byte(file: &mut dyn Reader, names: &Names) -> Result<Value> {
-    let enum_name = file.read_name()?;
+fn read_byte<'a>(
+    file: &mut dyn Reader,
+    enum_name: Name,
+    names: &mut Names<'a>,
+) -> Result<Value<'a>> {
     if &names[enum_name.id] == "None" {
         Ok(Value::Byte(file.read_u8()?))
     } else {
         //Its an enum, not a byte
-        Ok(Value::Enum(enum_name, file.read_name()?))
+        Ok(Value::Enum(enum_name, names.read_name(file)?))
     }
 }
 
-fn read_object(file: &mut dyn Reader, data_size: usize) -> Result<Value> {
+fn read_object<'a>(
+    file: &mut dyn Reader,
+    data_size: usize,
+    names: &mut Names<'a>,
+) -> Result<Value<'a>> {
     match data_size {
-        4 => Ok(Value::Int(file.read_i32()?)),
-        x if x >= 8 => {
-            let prop_type = file.read_u32()?;
-            if prop_type == 0 {
-                // ID
-                Ok(Value::Int(file.read_i32()?))
-            } else if prop_type == 1 {
-                // Path
-                let name = file.read_name()?;
-                Ok(Value::Name(name))
-            } else {
-                panic!("Unknown object prop type {}", prop_type);
-            }
-        }
-        _ => {
-            panic!("Reading object with data size {}", data_size);
+        4 if !names.asa => {
+            names.short_object_references = true;
+            Ok(Value::Int(file.read_i32()?))
         }
+        x if x >= 8 || names.asa => read_object_reference(file, names),
+        _ => Err(ArkError::InvalidData {
+            offset: file.stream_position()?,
+            message: format!("Object reference with data size {}", data_size),
+        }),
     }
 }
 
-fn read_struct(file: &mut dyn Reader, data_size: usize, names: &Rc<Names>) -> Result<Value> {
-    let name = file.read_name()?;
-    let end = file.seek(SeekFrom::Current(0))? + data_size as u64;
-    let struct_value = read_struct_value(file, name.id, names)?;
-    file.seek(SeekFrom::Start(end))?;
-    Ok(struct_value)
+/// An object id or path. Ark: Survival Ascended refers to objects by guid
+/// rather than by their index in the save.
+fn read_object_reference<'a>(file: &mut dyn Reader, names: &mut Names<'a>) -> Result<Value<'a>> {
+    let kind = if names.asa {
+        file.read_i16()? as i32
+    } else {
+        file.read_i32()?
+    };
+    match kind {
+        0 if names.asa => Ok(Value::Guid(file.read_u128()?)),
+        0 => Ok(Value::Int(file.read_i32()?)),
+        1 => Ok(Value::Name(names.read_name(file)?)),
+        kind => Err(ArkError::UnknownObjectReference {
+            offset: file.stream_position()? - if names.asa { 2 } else { 4 },
+            kind,
+        }),
+    }
 }
 
-fn read_struct_value(
-    file: &mut dyn Reader,
+fn read_struct<'a>(
+    file: &mut dyn Source<'a>,
+    struct_type: Name,
+    data_size: usize,
+    names: &mut Names<'a>,
+) -> Result<Value<'a>> {
+    // Ark's item ids are saved as bare fields in some versions and as a list
+    // of properties in others, which is always larger
+    if struct_type.id == names.item_netid_property && data_size == 8 {
+        return Ok(Value::ItemNetId(file.read_u32()?, file.read_u32()?));
+    }
+    read_struct_value(file, struct_type.id, names)
+}
+
+fn read_struct_value<'a>(
+    file: &mut dyn Source<'a>,
     struct_type_id: usize,
-    names: &Rc<Names>,
-) -> Result<Value> {
+    names: &mut Names<'a>,
+) -> Result<Value<'a>> {
+    let wide = names.asa;
     Ok(match struct_type_id {
-        _ if struct_type_id == names.vector_property => read_vector_struct(file)?,
-        _ if struct_type_id == names.rotator_property => read_vector_struct(file)?,
-        _ if struct_type_id == names.vector2d_property => read_vector2d_struct(file)?,
-        _ if struct_type_id == names.quat_property => read_quat_struct(file)?,
+        _ if struct_type_id == names.vector_property => read_vector_struct(file, wide)?,
+        _ if struct_type_id == names.rotator_property => read_rotator_struct(file, wide)?,
+        _ if struct_type_id == names.vector2d_property => read_vector2d_struct(file, wide)?,
+        _ if struct_type_id == names.quat_property => read_quat_struct(file, wide)?,
         _ if struct_type_id == names.color_property => read_color_struct(file)?,
         _ if struct_type_id == names.linear_color_property => read_linear_color_struct(file)?,
-        _ if struct_type_id == names.unique_netid_property => read_net_struct(file)?,
+        _ if struct_type_id == names.unique_netid_property => read_net_struct(file, wide)?,
+        _ if struct_type_id == names.int_point_property => {
+            Value::IntPoint(file.read_i32()?, file.read_i32()?)
+        }
+        _ if struct_type_id == names.int_vector_property => {
+            Value::IntVector(file.read_i32()?, file.read_i32()?, file.read_i32()?)
+        }
+        _ if struct_type_id == names.guid_property => Value::Guid(file.read_u128()?),
+        _ if struct_type_id == names.box_property => Value::Box {
+            min: read_vector(file, wide)?,
+            max: read_vector(file, wide)?,
+            valid: file.read_u8()? != 0,
+        },
+        _ if struct_type_id == names.transform_property => Value::Transform {
+            rotation: (
+                read_real(file, wide)?,
+                read_real(file, wide)?,
+                read_real(file, wide)?,
+                read_real(file, wide)?,
+            ),
+            translation: read_vector(file, wide)?,
+            scale: read_vector(file, wide)?,
+        },
         _ => {
             let mut properties = Properties::new();
             properties.read(file, names)?;
@@ -188,22 +506,79 @@ fn read_struct_value(
     })
 }
 
-fn read_text(file: &mut dyn Reader, data_size: usize) -> Result<Value> {
-    let mut buf = vec![0; data_size];
-    file.read_exact(buf.as_mut_slice())?;
-    let data = decode(buf).unwrap();
-    Ok(Value::String(String::from_utf8(data).unwrap()))
+fn read_text_value<'a>(file: &mut dyn Source<'a>, names: &Names) -> Result<Text<'a>> {
+    let flags = file.read_u32()?;
+    let offset = file.stream_position()?;
+    let history = match file.read_i8()? {
+        // Unreal Engine 5 saves whether there's a culture invariant string
+        Text::HISTORY_NONE if names.asa && file.read_i32()? != 0 => {
+            TextHistory::None(Some(read_string(file)?))
+        }
+        Text::HISTORY_NONE => TextHistory::None(None),
+        Text::HISTORY_BASE => TextHistory::Base {
+            namespace: read_string(file)?,
+            key: read_string(file)?,
+            source: read_string(file)?,
+        },
+        history_type @ Text::HISTORY_NAMED_FORMAT..=Text::HISTORY_ARGUMENT_FORMAT => {
+            let pattern = Box::new(read_text_value(file, names)?);
+            let count = file.read_i32()?.max(0) as usize;
+            let mut arguments = Vec::with_capacity(count.min(64));
+            for _ in 0..count {
+                let name = if history_type == Text::HISTORY_ORDERED_FORMAT {
+                    Cow::Borrowed("")
+                } else {
+                    read_string(file)?
+                };
+                arguments.push((name, read_format_argument(file, names)?));
+            }
+            TextHistory::Format {
+                history_type,
+                pattern,
+                arguments,
+            }
+        }
+        history_type => {
+            return Err(ArkError::InvalidData {
+                offset,
+                message: format!("Unsupported text history type {}", history_type),
+            })
+        }
+    };
+    Ok(Text { flags, history })
+}
+
+fn read_format_argument<'a>(
+    file: &mut dyn Source<'a>,
+    names: &Names,
+) -> Result<FormatArgument<'a>> {
+    let offset = file.stream_position()?;
+    Ok(match file.read_i8()? {
+        0 => FormatArgument::Int(file.read_i64()?),
+        1 => FormatArgument::UInt(file.read_u64()?),
+        2 => FormatArgument::Float(file.read_f32()?),
+        3 => FormatArgument::Double(file.read_f64()?),
+        4 => FormatArgument::Text(read_text_value(file, names)?),
+        5 => FormatArgument::Gender(file.read_u8()?),
+        kind => {
+            return Err(ArkError::InvalidData {
+                offset,
+                message: format!("Unknown text format argument type {}", kind),
+            })
+        }
+    })
 }
 
-fn read_color_struct(file: &mut dyn Reader) -> Result<Value> {
-    let b = file.read_f32()?;
-    let g = file.read_f32()?;
-    let r = file.read_f32()?;
-    let a = file.read_f32()?;
+/// A color of four bytes in BGRA order, scaled to match `LinearColor`.
+fn read_color_struct<'a>(file: &mut dyn Reader) -> Result<Value<'a>> {
+    let b = file.read_u8()? as f32 / 255.0;
+    let g = file.read_u8()? as f32 / 255.0;
+    let r = file.read_u8()? as f32 / 255.0;
+    let a = file.read_u8()? as f32 / 255.0;
     Ok(Value::RGBA(r, g, b, a))
 }
 
-fn read_linear_color_struct(file: &mut dyn Reader) -> Result<Value> {
+fn read_linear_color_struct<'a>(file: &mut dyn Reader) -> Result<Value<'a>> {
     Ok(Value::RGBA(
         file.read_f32()?,
         file.read_f32()?,
@@ -212,28 +587,450 @@ fn read_linear_color_struct(file: &mut dyn Reader) -> Result<Value> {
     ))
 }
 
-fn read_quat_struct(file: &mut dyn Reader) -> Result<Value> {
+fn read_quat_struct<'a>(file: &mut dyn Reader, wide: bool) -> Result<Value<'a>> {
     Ok(Value::Quat(
-        file.read_f32()?,
-        file.read_f32()?,
-        file.read_f32()?,
-        file.read_f32()?,
+        read_real(file, wide)?,
+        read_real(file, wide)?,
+        read_real(file, wide)?,
+        read_real(file, wide)?,
+    ))
+}
+
+/// The original game saves the size of the id, where Unreal Engine 5 saves
+/// a byte before the online subsystem and the id as bytes.
+fn read_net_struct<'a>(file: &mut dyn Source<'a>, wide: bool) -> Result<Value<'a>> {
+    if !wide {
+        return Ok(Value::UniqueNetId {
+            size: file.read_i32()?,
+            net_type: Cow::Borrowed(""),
+            id: read_string(file)?,
+        });
+    }
+    let size = file.read_u8()? as i32;
+    let net_type = read_string(file)?;
+    let len = file.read_u8()? as usize;
+    let id = file
+        .read_bytes(len)?
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    Ok(Value::UniqueNetId {
+        size,
+        net_type,
+        id: Cow::Owned(id),
+    })
+}
+
+fn read_rotator_struct<'a>(file: &mut dyn Reader, wide: bool) -> Result<Value<'a>> {
+    Ok(Value::Rotator(
+        read_real(file, wide)?,
+        read_real(file, wide)?,
+        read_real(file, wide)?,
     ))
 }
 
-fn read_net_struct(file: &mut dyn Reader) -> Result<Value> {
-    file.read_i32()?;
-    Ok(Value::String(file.read_str()?))
+fn read_vector(file: &mut dyn Reader, wide: bool) -> Result<(f32, f32, f32)> {
+    Ok((
+        read_real(file, wide)?,
+        read_real(file, wide)?,
+        read_real(file, wide)?,
+    ))
 }
 
-fn read_vector_struct(file: &mut dyn Reader) -> Result<Value> {
+fn read_vector_struct<'a>(file: &mut dyn Reader, wide: bool) -> Result<Value<'a>> {
     Ok(Value::Vector(
-        file.read_f32()?,
-        file.read_f32()?,
-        file.read_f32()?,
+        read_real(file, wide)?,
+        read_real(file, wide)?,
+        read_real(file, wide)?,
+    ))
+}
+
+fn read_vector2d_struct<'a>(file: &mut dyn Reader, wide: bool) -> Result<Value<'a>> {
+    Ok(Value::Vector2D(
+        read_real(file, wide)?,
+        read_real(file, wide)?,
     ))
 }
 
-fn read_vector2d_struct(file: &mut dyn Reader) -> Result<Value> {
-    Ok(Value::Vector2D(file.read_f32()?, file.read_f32()?))
+/// Unreal Engine 5 stores vector components as doubles.
+fn read_real(file: &mut dyn Reader, wide: bool) -> Result<f32> {
+    if wide {
+        Ok(file.read_f64()? as f32)
+    } else {
+        file.read_f32()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::{name, names, property};
+    use crate::io::{ArrayReader, Writer};
+    use crate::properties::NameWriter;
+
+    /// Reads a list of properties, checking it's written back as the same
+    /// bytes.
+    fn read<'a>(data: &'a [u8], names: &mut Names<'a>) -> Properties<'a> {
+        let mut properties = Properties::new();
+        properties.read(&mut ArrayReader::new(data), names).unwrap();
+        assert_eq!(write(&properties, names), data);
+        properties
+    }
+
+    fn write(properties: &Properties, names: &Names) -> Vec<u8> {
+        let mut written = Vec::new();
+        properties
+            .write(&mut written, &mut NameWriter::new(names))
+            .unwrap();
+        written
+    }
+
+    fn end(mut file: Vec<u8>) -> Vec<u8> {
+        file.write_name(&name("None", 0)).unwrap();
+        file
+    }
+
+    fn id(property: &str) -> usize {
+        name(property, 0).id
+    }
+
+    #[test]
+    fn unknown_types() {
+        let mut file = Vec::new();
+        property(
+            &mut file,
+            "Mod",
+            "SoftObjectProperty",
+            &[],
+            &[1, 2, 3, 4, 5],
+        );
+        let mut tag = Vec::new();
+        tag.write_name(&name("SoftObjectProperty", 0)).unwrap();
+        property(
+            &mut file,
+            "Mods",
+            "ArrayProperty",
+            &tag,
+            &[1, 0, 0, 0, 9, 9],
+        );
+        let data = end(file);
+
+        let mut names = names();
+        let properties = read(&data, &mut names);
+        assert!(matches!(
+            properties.get(&id("Mod")),
+            Some(Value::Unknown { type_name, raw })
+                if type_name.id == id("SoftObjectProperty") && raw == &[1, 2, 3, 4, 5]
+        ));
+        assert!(matches!(
+            properties.get(&id("Mods")),
+            Some(Value::Unknown { type_name, raw })
+                if type_name.id == id("SoftObjectProperty") && raw == &[1, 0, 0, 0, 9, 9]
+        ));
+    }
+
+    #[test]
+    fn values_written_differently_are_kept_raw() {
+        let mut file = Vec::new();
+        // An ASCII string saved as UTF-16
+        let mut data = Vec::new();
+        data.write_i32(-3).unwrap();
+        data.write_bytes(&[b'H', 0, b'i', 0, 0, 0]).unwrap();
+        property(&mut file, "Note", "StrProperty", &[], &data);
+        // A struct with bytes after its properties
+        let mut tag = Vec::new();
+        tag.write_name(&name("Dodo_Character_BP_C", 0)).unwrap();
+        let data = end(Vec::new());
+        property(
+            &mut file,
+            "Owner",
+            "StructProperty",
+            &tag,
+            &[&data[..], &[7]].concat(),
+        );
+        // A bool array holding something other than 0 or 1
+        let mut tag = Vec::new();
+        tag.write_name(&name("BoolProperty", 0)).unwrap();
+        property(&mut file, "Stats", "ArrayProperty", &tag, &[1, 0, 0, 0, 2]);
+        let data = end(file);
+
+        let mut names = names();
+        let properties = read(&data, &mut names);
+        assert!(matches!(
+            properties.get(&id("Note")),
+            Some(Value::Unknown { type_name, raw })
+                if type_name.id == id("StrProperty") && raw.len() == 10
+        ));
+        assert!(matches!(
+            properties.get(&id("Owner")),
+            Some(Value::Unknown { type_name, raw })
+                if type_name.id == id("StructProperty") && raw.len() == 9
+        ));
+        assert!(matches!(
+            properties.get(&id("Stats")),
+            Some(Value::Unknown { type_name, .. }) if type_name.id == id("BoolProperty")
+        ));
+    }
+
+    #[test]
+    fn maps_and_sets() {
+        let mut file = Vec::new();
+        let mut tag = Vec::new();
+        tag.write_name(&name("IntProperty", 0)).unwrap();
+        tag.write_name(&name("ObjectProperty", 0)).unwrap();
+        let mut data = Vec::new();
+        for v in &[0, 1, 5, 0, 1] {
+            data.write_i32(*v).unwrap();
+        }
+        property(&mut file, "Levels", "MapProperty", &tag, &data);
+
+        let mut tag = Vec::new();
+        tag.write_name(&name("NameProperty", 0)).unwrap();
+        let mut data = Vec::new();
+        data.write_i32(0).unwrap();
+        data.write_i32(2).unwrap();
+        data.write_name(&name("Mod", 0)).unwrap();
+        data.write_name(&name("Mods", 1)).unwrap();
+        property(&mut file, "Tags", "SetProperty", &tag, &data);
+
+        // Keys removed from the defaults aren't decoded
+        let mut tag = Vec::new();
+        tag.write_name(&name("IntProperty", 0)).unwrap();
+        property(
+            &mut file,
+            "Moods",
+            "SetProperty",
+            &tag,
+            &[1, 0, 0, 0, 3, 0, 0, 0, 0, 0, 0, 0],
+        );
+        let data = end(file);
+
+        let mut names = names();
+        let properties = read(&data, &mut names);
+        assert!(matches!(
+            properties.get(&id("Levels")),
+            Some(Value::Map(entries))
+                if matches!(entries.as_slice(), [(Value::Int(5), Value::Int(1))])
+        ));
+        assert!(matches!(
+            properties.get(&id("Tags")),
+            Some(Value::Set(items)) if matches!(
+                items.as_slice(),
+                [Value::Name(a), Value::Name(b)] if a.instance == 0 && b.instance == 1
+            )
+        ));
+        assert!(matches!(
+            properties.get(&id("Moods")),
+            Some(Value::Unknown { type_name, .. }) if type_name.id == id("SetProperty")
+        ));
+    }
+
+    #[test]
+    fn enum_arrays() {
+        let mut tag = Vec::new();
+        tag.write_name(&name("ByteProperty", 0)).unwrap();
+        let mut data = Vec::new();
+        data.write_i32(2).unwrap();
+        data.write_name(&name("EDinoMood", 1)).unwrap();
+        data.write_name(&name("EDinoMood", 2)).unwrap();
+        let mut file = Vec::new();
+        property(&mut file, "Moods", "ArrayProperty", &tag, &data);
+        property(
+            &mut file,
+            "Stats",
+            "ArrayProperty",
+            &tag,
+            &[2, 0, 0, 0, 1, 2],
+        );
+        let data = end(file);
+
+        let mut names = names();
+        let properties = read(&data, &mut names);
+        assert!(matches!(
+            properties.get(&id("Moods")),
+            Some(Value::ArrayOfName(items)) if items.len() == 2 && items[1].instance == 2
+        ));
+        assert!(matches!(
+            properties.get(&id("Stats")),
+            Some(Value::ArrayOfU8(items)) if items == &[1, 2]
+        ));
+    }
+
+    #[test]
+    fn struct_arrays() {
+        let mut tag = Vec::new();
+        tag.write_name(&name("StructProperty", 0)).unwrap();
+        let mut data = Vec::new();
+        data.write_i32(2).unwrap();
+        for team in &[[1, 0, 0, 0], [2, 0, 0, 0]] {
+            property(&mut data, "TargetingTeam", "IntProperty", &[], team);
+            data.write_name(&name("None", 0)).unwrap();
+        }
+        let mut file = Vec::new();
+        property(&mut file, "Ancestors", "ArrayProperty", &tag, &data);
+        let colors = [2, 0, 0, 0, 0, 0, 255, 255, 255, 0, 0, 255];
+        property(&mut file, "Colors", "ArrayProperty", &tag, &colors);
+        let mut locations = Vec::new();
+        locations.write_i32(2).unwrap();
+        for v in &[1.0f32, 2.0, 3.0, 4.0, 5.0, 6.0] {
+            locations.write_f32(*v).unwrap();
+        }
+        property(&mut file, "Locations", "ArrayProperty", &tag, &locations);
+        // Both a LinearColor and a Quat are 16 bytes
+        let mut data = Vec::new();
+        data.write_i32(1).unwrap();
+        for v in &[0.5f32, 0.25, 0.0, 1.0] {
+            data.write_f32(*v).unwrap();
+        }
+        let colours = "CustomColours_60_7D3267C846B277953C0C41AEBD54FBCB";
+        property(&mut file, colours, "ArrayProperty", &tag, &data);
+        property(&mut file, "Rotations", "ArrayProperty", &tag, &data);
+        let data = end(file);
+
+        let mut names = names();
+        let mut properties = read(&data, &mut names);
+        let ancestors = properties.get_structs(&id("Ancestors"));
+        let team = id("TargetingTeam");
+        let teams: Vec<_> = ancestors.iter().map(|a| a.get_i32(&team)).collect();
+        assert_eq!(teams, vec![Some(1), Some(2)]);
+        assert!(matches!(
+            properties.get(&id("Colors")),
+            Some(Value::ArrayOfStruct(items)) if matches!(
+                items.as_slice(),
+                [Value::RGBA(r, _, _, _), Value::RGBA(_, _, b, _)] if *r == 1.0 && *b == 1.0
+            )
+        ));
+
+        assert!(matches!(
+            properties.get(&id(colours)),
+            Some(Value::ArrayOfStruct(items))
+                if matches!(items.as_slice(), [Value::RGBA(r, g, _, _)] if *r == 0.5 && *g == 0.25)
+        ));
+        assert!(matches!(
+            properties.get(&id("Locations")),
+            Some(Value::ArrayOfStruct(items)) if matches!(
+                items.as_slice(),
+                [Value::Vector(x, _, _), Value::Vector(_, _, z)] if *x == 1.0 && *z == 6.0
+            )
+        ));
+        // Unnamed arrays of 16 byte structs are read as colors
+        assert!(matches!(
+            properties.get(&id("Rotations")),
+            Some(Value::ArrayOfStruct(items)) if matches!(items.as_slice(), [Value::RGBA(..)])
+        ));
+
+        match properties.get_mut(&id("Colors")) {
+            Some(Value::ArrayOfStruct(items)) => items.push(Value::RGBA(0.0, 1.0, 0.0, 1.0)),
+            _ => panic!("Colors isn't an array of structs"),
+        }
+        let written = write(&properties, &names);
+        let properties = read(&written, &mut names);
+        assert!(matches!(
+            properties.get(&id("Colors")),
+            Some(Value::ArrayOfStruct(items)) if items.len() == 3
+        ));
+    }
+
+    #[test]
+    fn text() {
+        let mut file = Vec::new();
+        let mut data = Vec::new();
+        data.write_u32(0).unwrap();
+        data.write_i8(0).unwrap();
+        for v in &["Signs", "A1B2", "Welcome"] {
+            data.write_str(v).unwrap();
+        }
+        property(&mut file, "SignText", "TextProperty", &[], &data);
+
+        let mut data = Vec::new();
+        data.write_u32(0).unwrap();
+        data.write_i8(1).unwrap();
+        data.write_u32(2).unwrap();
+        data.write_i8(0).unwrap();
+        for v in &["Labels", "C3D4", "{Count} dodos"] {
+            data.write_str(v).unwrap();
+        }
+        data.write_i32(1).unwrap();
+        data.write_str("Count").unwrap();
+        data.write_i8(0).unwrap();
+        data.write_i64(3).unwrap();
+        property(&mut file, "Label", "TextProperty", &[], &data);
+        let data = end(file);
+
+        let mut names = names();
+        let properties = read(&data, &mut names);
+        assert!(matches!(
+            properties.get(&id("SignText")),
+            Some(Value::Text(Text {
+                history: TextHistory::Base { namespace, key, source },
+                ..
+            })) if namespace == "Signs" && key == "A1B2" && source == "Welcome"
+        ));
+        let label = properties.get(&id("Label")).unwrap();
+        assert!(matches!(
+            label,
+            Value::Text(Text {
+                history: TextHistory::Format { arguments, .. },
+                ..
+            }) if matches!(arguments.as_slice(), [(name, FormatArgument::Int(3))] if name == "Count")
+        ));
+        assert_eq!(label.to_string(), "3 dodos");
+    }
+
+    #[test]
+    fn native_structs() {
+        let mut file = Vec::new();
+        let mut tag = Vec::new();
+        tag.write_name(&name("Rotator", 0)).unwrap();
+        let mut data = Vec::new();
+        for v in &[10.0f32, 90.0, 0.0] {
+            data.write_f32(*v).unwrap();
+        }
+        property(&mut file, "Rotation", "StructProperty", &tag, &data);
+
+        let mut tag = Vec::new();
+        tag.write_name(&name("UniqueNetIdRepl", 0)).unwrap();
+        let mut data = Vec::new();
+        data.write_i32(8).unwrap();
+        data.write_str("76561197960287930").unwrap();
+        property(&mut file, "PlayerId", "StructProperty", &tag, &data);
+
+        let mut tag = Vec::new();
+        tag.write_name(&name("ItemNetID", 0)).unwrap();
+        let mut data = Vec::new();
+        data.write_u32(12).unwrap();
+        data.write_u32(34).unwrap();
+        property(&mut file, "ItemId", "StructProperty", &tag, &data);
+
+        let mut tag = Vec::new();
+        tag.write_name(&name("Box", 0)).unwrap();
+        let mut data = Vec::new();
+        for v in &[-1.0f32, -2.0, -3.0, 1.0, 2.0, 3.0] {
+            data.write_f32(*v).unwrap();
+        }
+        data.write_u8(1).unwrap();
+        property(&mut file, "Bounds", "StructProperty", &tag, &data);
+        let data = end(file);
+
+        let mut names = names();
+        let properties = read(&data, &mut names);
+        assert!(matches!(
+            properties.get(&id("Rotation")),
+            Some(Value::Rotator(pitch, yaw, _)) if *pitch == 10.0 && *yaw == 90.0
+        ));
+        assert!(matches!(
+            properties.get(&id("PlayerId")),
+            Some(Value::UniqueNetId { size: 8, net_type, id })
+                if net_type.is_empty() && id == "76561197960287930"
+        ));
+        assert!(matches!(
+            properties.get(&id("ItemId")),
+            Some(Value::ItemNetId(12, 34))
+        ));
+        assert!(matches!(
+            properties.get(&id("Bounds")),
+            Some(Value::Box { min, max, valid: true })
+                if *min == (-1.0, -2.0, -3.0) && *max == (1.0, 2.0, 3.0)
+        ));
+    }
 }
@@ -0,0 +1,94 @@
+use serde::Serialize;
+use std::borrow::Cow;
+use std::fmt::{Display, Formatter};
+
+/// Localizable text, such as an item's custom name or the text of a sign.
+#[derive(Debug, Clone, Serialize)]
+pub struct Text<'a> {
+    pub flags: u32,
+    pub history: TextHistory<'a>,
+}
+
+/// How the text was made, saved as a history type before its fields.
+#[derive(Debug, Clone, Serialize)]
+pub enum TextHistory<'a> {
+    /// History type -1, text that isn't localized, with the string newer
+    /// versions save for it.
+    None(Option<Cow<'a, str>>),
+    /// History type 0, a source string found by namespace and key.
+    Base {
+        namespace: Cow<'a, str>,
+        key: Cow<'a, str>,
+        source: Cow<'a, str>,
+    },
+    /// History types 1 to 3, a pattern filled in with named, ordered or
+    /// argument formatting. Ordered arguments have empty names.
+    Format {
+        history_type: i8,
+        pattern: Box<Text<'a>>,
+        arguments: Vec<(Cow<'a, str>, FormatArgument<'a>)>,
+    },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub enum FormatArgument<'a> {
+    Int(i64),
+    UInt(u64),
+    Float(f32),
+    Double(f64),
+    Text(Text<'a>),
+    Gender(u8),
+}
+
+impl<'a> Text<'a> {
+    pub const HISTORY_NONE: i8 = -1;
+    pub const HISTORY_BASE: i8 = 0;
+    pub const HISTORY_NAMED_FORMAT: i8 = 1;
+    pub const HISTORY_ORDERED_FORMAT: i8 = 2;
+    pub const HISTORY_ARGUMENT_FORMAT: i8 = 3;
+
+    pub fn history_type(&self) -> i8 {
+        match &self.history {
+            TextHistory::None(_) => Text::HISTORY_NONE,
+            TextHistory::Base { .. } => Text::HISTORY_BASE,
+            TextHistory::Format { history_type, .. } => *history_type,
+        }
+    }
+}
+
+/// The text as shown in game, with format arguments filled in.
+impl<'a> Display for Text<'a> {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), std::fmt::Error> {
+        match &self.history {
+            TextHistory::None(v) => f.write_str(v.as_deref().unwrap_or("")),
+            TextHistory::Base { source, .. } => f.write_str(source),
+            TextHistory::Format {
+                pattern, arguments, ..
+            } => {
+                let mut text = pattern.to_string();
+                for (i, (name, argument)) in arguments.iter().enumerate() {
+                    let placeholder = if name.is_empty() {
+                        format!("{{{}}}", i)
+                    } else {
+                        format!("{{{}}}", name)
+                    };
+                    text = text.replace(&placeholder, &argument.to_string());
+                }
+                f.write_str(&text)
+            }
+        }
+    }
+}
+
+impl<'a> Display for FormatArgument<'a> {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), std::fmt::Error> {
+        match self {
+            FormatArgument::Int(v) => f.write_fmt(format_args!("{}", v)),
+            FormatArgument::UInt(v) => f.write_fmt(format_args!("{}", v)),
+            FormatArgument::Float(v) => f.write_fmt(format_args!("{}", v)),
+            FormatArgument::Double(v) => f.write_fmt(format_args!("{}", v)),
+            FormatArgument::Text(v) => f.write_fmt(format_args!("{}", v)),
+            FormatArgument::Gender(v) => f.write_fmt(format_args!("{}", v)),
+        }
+    }
+}
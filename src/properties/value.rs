@@ -1,46 +1,136 @@
 use crate::io::Name;
 use serde::Serialize;
+use std::borrow::Cow;
 use std::fmt::{Display, Formatter};
 
-use super::Properties;
+use super::{Properties, Text};
 
-#[derive(Debug, Serialize)]
-pub enum Value {
+#[derive(Debug, Clone, Serialize)]
+pub enum Value<'a> {
     ArrayOfF32(Vec<f32>),
     ArrayOfF64(Vec<f64>),
     ArrayOfI16(Vec<i16>),
     ArrayOfI32(Vec<i32>),
     ArrayOfI8(Vec<i8>),
     ArrayOfName(Vec<Name>),
-    ArrayOfObject(Vec<Value>),
-    ArrayOfStruct(Vec<Value>),
-    ArrayOfStr(Vec<String>),
+    ArrayOfObject(Vec<Value<'a>>),
+    ArrayOfStruct(Vec<Value<'a>>),
+    ArrayOfStr(Vec<Cow<'a, str>>),
     ArrayOfU16(Vec<u16>),
     ArrayOfU32(Vec<u32>),
     ArrayOfU64(Vec<u64>),
     ArrayOfU8(Vec<u8>),
     ArrayOfBool(Vec<bool>),
     Bool(bool),
+    Box {
+        min: (f32, f32, f32),
+        max: (f32, f32, f32),
+        valid: bool,
+    },
     Byte(u8),
     Double(f64),
     Enum(Name, Name),
     Float(f32),
+    Guid(u128),
     Int16(i16),
     Int8(i8),
     Int(i32),
+    IntPoint(i32, i32),
+    IntVector(i32, i32, i32),
+    /// The two halves of an item's id, where the original game saves it as
+    /// bare fields.
+    ItemNetId(u32, u32),
+    /// The entries of a map, in the order they were saved.
+    Map(Vec<(Value<'a>, Value<'a>)>),
     Name(Name),
-    Properties(Properties),
+    Properties(Properties<'a>),
     Quat(f32, f32, f32, f32),
+    #[allow(clippy::upper_case_acronyms)]
     RGBA(f32, f32, f32, f32),
-    String(String),
+    /// Pitch, yaw and roll in degrees.
+    Rotator(f32, f32, f32),
+    Set(Vec<Value<'a>>),
+    String(Cow<'a, str>),
+    Text(Text<'a>),
+    Transform {
+        rotation: (f32, f32, f32, f32),
+        translation: (f32, f32, f32),
+        scale: (f32, f32, f32),
+    },
     UInt16(u16),
     UInt32(u32),
     UInt64(u64),
+    /// A player's online id. `net_type` is the online subsystem, which is
+    /// empty in saves from the original game. `size` is saved before the id,
+    /// which the original game sets to 8 for Steam ids.
+    UniqueNetId {
+        size: i32,
+        net_type: Cow<'a, str>,
+        id: Cow<'a, str>,
+    },
+    /// A property or array element of a type that isn't decoded, holding its
+    /// encoded value.
+    Unknown {
+        type_name: Name,
+        raw: Vec<u8>,
+    },
     Vector(f32, f32, f32),
     Vector2D(f32, f32),
 }
 
-impl Display for Value {
+impl<'a> Value<'a> {
+    /// The name of the variant, for describing values in errors.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Value::ArrayOfF32(..) => "ArrayOfF32",
+            Value::ArrayOfF64(..) => "ArrayOfF64",
+            Value::ArrayOfI16(..) => "ArrayOfI16",
+            Value::ArrayOfI32(..) => "ArrayOfI32",
+            Value::ArrayOfI8(..) => "ArrayOfI8",
+            Value::ArrayOfName(..) => "ArrayOfName",
+            Value::ArrayOfObject(..) => "ArrayOfObject",
+            Value::ArrayOfStruct(..) => "ArrayOfStruct",
+            Value::ArrayOfStr(..) => "ArrayOfStr",
+            Value::ArrayOfU16(..) => "ArrayOfU16",
+            Value::ArrayOfU32(..) => "ArrayOfU32",
+            Value::ArrayOfU64(..) => "ArrayOfU64",
+            Value::ArrayOfU8(..) => "ArrayOfU8",
+            Value::ArrayOfBool(..) => "ArrayOfBool",
+            Value::Bool(..) => "Bool",
+            Value::Box { .. } => "Box",
+            Value::Byte(..) => "Byte",
+            Value::Double(..) => "Double",
+            Value::Enum(..) => "Enum",
+            Value::Float(..) => "Float",
+            Value::Guid(..) => "Guid",
+            Value::Int16(..) => "Int16",
+            Value::Int8(..) => "Int8",
+            Value::Int(..) => "Int",
+            Value::IntPoint(..) => "IntPoint",
+            Value::IntVector(..) => "IntVector",
+            Value::ItemNetId(..) => "ItemNetId",
+            Value::Map(..) => "Map",
+            Value::Name(..) => "Name",
+            Value::Properties(..) => "Properties",
+            Value::Quat(..) => "Quat",
+            Value::RGBA(..) => "RGBA",
+            Value::Rotator(..) => "Rotator",
+            Value::Set(..) => "Set",
+            Value::String(..) => "String",
+            Value::Text(..) => "Text",
+            Value::Transform { .. } => "Transform",
+            Value::UInt16(..) => "UInt16",
+            Value::UInt32(..) => "UInt32",
+            Value::UInt64(..) => "UInt64",
+            Value::UniqueNetId { .. } => "UniqueNetId",
+            Value::Unknown { .. } => "Unknown",
+            Value::Vector(..) => "Vector",
+            Value::Vector2D(..) => "Vector2D",
+        }
+    }
+}
+
+impl<'a> Display for Value<'a> {
     fn fmt(&self, f: &mut Formatter) -> Result<(), std::fmt::Error> {
         match self {
             Value::ArrayOfF32(v) => f.write_fmt(format_args!("{:?}", v)),
@@ -58,13 +148,32 @@ impl Display for Value {
             Value::ArrayOfU8(v) => f.write_fmt(format_args!("{:?}", v)),
             Value::ArrayOfBool(v) => f.write_fmt(format_args!("{:?}", v)),
             Value::Bool(v) => f.write_fmt(format_args!("{}", v)),
+            Value::Box { min, max, valid } => {
+                f.write_fmt(format_args!("{:?}-{:?} {}", min, max, valid))
+            }
             Value::Byte(v) => f.write_fmt(format_args!("{}", v)),
             Value::Double(v) => f.write_fmt(format_args!("{}", v)),
             Value::Enum(ref name, ref v) => f.write_fmt(format_args!("{}: {}", name.id, v.id)),
             Value::Float(v) => f.write_fmt(format_args!("{}", v)),
+            Value::Guid(v) => {
+                let part = |shift: u32| (v >> shift) as u32;
+                f.write_fmt(format_args!(
+                    "{:08X}-{:04X}-{:04X}-{:04X}-{:04X}{:08X}",
+                    part(0),
+                    part(32) >> 16,
+                    part(32) & 0xffff,
+                    part(64) >> 16,
+                    part(64) & 0xffff,
+                    part(96)
+                ))
+            }
             Value::Int16(v) => f.write_fmt(format_args!("{}", v)),
             Value::Int8(v) => f.write_fmt(format_args!("{}", v)),
             Value::Int(v) => f.write_fmt(format_args!("{}", v)),
+            Value::IntPoint(x, y) => f.write_fmt(format_args!("({},{})", x, y)),
+            Value::IntVector(x, y, z) => f.write_fmt(format_args!("({},{},{})", x, y, z)),
+            Value::ItemNetId(id1, id2) => f.write_fmt(format_args!("({},{})", id1, id2)),
+            Value::Map(v) => f.write_fmt(format_args!("{:?}", v)),
             Value::Name(ref name) => f.write_fmt(format_args!("{}", name.id)),
             Value::Properties(p) => {
                 f.write_str("{\n")?;
@@ -81,10 +190,27 @@ impl Display for Value {
                 (b * 255f32) as isize,
                 (a * 255f32) as isize,
             )),
+            Value::Rotator(pitch, yaw, roll) => {
+                f.write_fmt(format_args!("({},{},{})", pitch, yaw, roll))
+            }
+            Value::Set(v) => f.write_fmt(format_args!("{:?}", v)),
             Value::String(ref v) => f.write_str(v),
+            Value::Text(v) => f.write_fmt(format_args!("{}", v)),
+            Value::Transform {
+                rotation,
+                translation,
+                scale,
+            } => f.write_fmt(format_args!("{:?} {:?} {:?}", rotation, translation, scale)),
             Value::UInt16(v) => f.write_fmt(format_args!("{}", v)),
             Value::UInt32(v) => f.write_fmt(format_args!("{}", v)),
             Value::UInt64(v) => f.write_fmt(format_args!("{}", v)),
+            Value::UniqueNetId { net_type, id, .. } if net_type.is_empty() => f.write_str(id),
+            Value::UniqueNetId { net_type, id, .. } => {
+                f.write_fmt(format_args!("{}: {}", net_type, id))
+            }
+            Value::Unknown { type_name, raw } => {
+                f.write_fmt(format_args!("{}: {} bytes", type_name.id, raw.len()))
+            }
             Value::Vector(x, y, z) => f.write_fmt(format_args!("({},{},{})", x, y, z)),
             Value::Vector2D(x, y) => f.write_fmt(format_args!("({},{})", x, y)),
         }
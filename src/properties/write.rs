@@ -0,0 +1,473 @@
+use crate::io::{Name, Writer};
+use crate::object::Names;
+use crate::properties::{FormatArgument, Properties, Property, Text, TextHistory, Value};
+use crate::{ArkError, Result};
+
+/// Writes names as indexes into the save's names table, tracking the highest
+/// id written so names added after the save was read can be appended to it.
+pub(crate) struct NameWriter<'n, 'a> {
+    pub names: &'n Names<'a>,
+    pub max_id: usize,
+}
+
+impl<'n, 'a> NameWriter<'n, 'a> {
+    pub fn new(names: &'n Names<'a>) -> Self {
+        NameWriter { names, max_id: 0 }
+    }
+
+    pub fn write(&mut self, file: &mut dyn Writer, name: &Name) -> Result<()> {
+        self.max_id = self.max_id.max(name.id);
+        file.write_name(name)
+    }
+
+    fn write_none(&mut self, file: &mut dyn Writer) -> Result<()> {
+        let none = self.id("None")?;
+        self.write(
+            file,
+            &Name {
+                id: none,
+                instance: 0,
+            },
+        )
+    }
+
+    fn id(&self, name: &str) -> Result<usize> {
+        self.names
+            .get_name_id(name)
+            .copied()
+            .ok_or_else(|| ArkError::UnknownName {
+                name: String::from(name),
+            })
+    }
+}
+
+impl<'a> Properties<'a> {
+    /// Writes every property in the order they were read, followed by the
+    /// `None` name that ends the list.
+    pub(crate) fn write(&self, file: &mut dyn Writer, names: &mut NameWriter) -> Result<()> {
+        for id in &self.order {
+            for property in self.props.get(id).into_iter().flatten() {
+                property
+                    .write(file, names)
+                    .map_err(|e| e.in_property(&names.names[*id]))?;
+            }
+        }
+        names.write_none(file)
+    }
+}
+
+impl<'a> Property<'a> {
+    fn write(&self, file: &mut dyn Writer, names: &mut NameWriter) -> Result<()> {
+        names.write(file, &self.name)?;
+        names.write(file, &self.type_name)?;
+
+        // Fields of the tag before the value aren't counted in its size
+        let mut tag = Vec::new();
+        let mut data = Vec::new();
+        write_value(&mut tag, &mut data, self, names)?;
+        file.write_u32(data.len() as u32)?;
+        file.write_u32(self.ind)?;
+        file.write_bytes(&tag)?;
+        file.write_bytes(&data)
+    }
+}
+
+impl<'a> Property<'a> {
+    /// Checks the value can be written as the property's type.
+    pub(crate) fn check(&self, names: &Names) -> Result<()> {
+        write_value(
+            &mut Vec::new(),
+            &mut Vec::new(),
+            self,
+            &mut NameWriter::new(names),
+        )
+    }
+}
+
+fn mismatch(names: &NameWriter, type_id: usize, value: &Value) -> ArkError {
+    ArkError::TypeMismatch {
+        type_name: String::from(&names.names[type_id]),
+        value: value.to_string(),
+    }
+}
+
+fn inner_type(property: &Property, names: &NameWriter) -> Result<Name> {
+    tag_type(property.inner_type, "an inner type", property, names)
+}
+
+fn tag_type(
+    type_name: Option<Name>,
+    description: &str,
+    property: &Property,
+    names: &NameWriter,
+) -> Result<Name> {
+    type_name.ok_or_else(|| ArkError::InvalidData {
+        offset: 0,
+        message: format!(
+            "{} without {}",
+            &names.names[property.type_name.id], description
+        ),
+    })
+}
+
+fn write_value(
+    tag: &mut Vec<u8>,
+    data: &mut Vec<u8>,
+    property: &Property,
+    names: &mut NameWriter,
+) -> Result<()> {
+    let type_id = property.type_name.id;
+    let value = &property.value;
+    let n = names.names;
+    match type_id {
+        _ if type_id == n.array_property => {
+            let inner_type = inner_type(property, names)?;
+            names.write(tag, &inner_type)?;
+            write_array(data, inner_type.id, property.value_type, value, names)
+        }
+        _ if type_id == n.bool_property => match value {
+            Value::Bool(v) => tag.write_u8(*v as u8),
+            _ => Err(mismatch(names, type_id, value)),
+        },
+        _ if type_id == n.byte_property => match value {
+            Value::Byte(v) => {
+                names.write_none(tag)?;
+                data.write_u8(*v)
+            }
+            Value::Enum(enum_name, v) => {
+                names.write(tag, enum_name)?;
+                names.write(data, v)
+            }
+            Value::Unknown { type_name, raw } if type_name.id == type_id => {
+                names.write(tag, &inner_type(property, names)?)?;
+                data.write_bytes(raw)
+            }
+            _ => Err(mismatch(names, type_id, value)),
+        },
+        _ if type_id == n.map_property => {
+            let key_type = inner_type(property, names)?;
+            let value_type = tag_type(property.value_type, "a value type", property, names)?;
+            names.write(tag, &key_type)?;
+            names.write(tag, &value_type)?;
+            match value {
+                Value::Map(entries) => {
+                    data.write_i32(0)?;
+                    data.write_i32(entries.len() as i32)?;
+                    for (key, value) in entries {
+                        write_container_element(data, key_type.id, key, names)?;
+                        write_container_element(data, value_type.id, value, names)?;
+                    }
+                    Ok(())
+                }
+                _ => write_element(data, type_id, value, names),
+            }
+        }
+        _ if type_id == n.set_property => {
+            let element_type = inner_type(property, names)?;
+            names.write(tag, &element_type)?;
+            match value {
+                Value::Set(items) => {
+                    data.write_i32(0)?;
+                    data.write_i32(items.len() as i32)?;
+                    for item in items {
+                        write_container_element(data, element_type.id, item, names)?;
+                    }
+                    Ok(())
+                }
+                _ => write_element(data, type_id, value, names),
+            }
+        }
+        _ if type_id == n.struct_property => {
+            let inner_type = inner_type(property, names)?;
+            names.write(tag, &inner_type)?;
+            match value {
+                Value::Unknown { type_name, raw } if type_name.id == type_id => {
+                    data.write_bytes(raw)
+                }
+                _ => write_struct_value(data, inner_type.id, value, names),
+            }
+        }
+        _ if type_id == n.object_property && n.short_object_references => match value {
+            Value::Int(v) => data.write_i32(*v),
+            _ => write_element(data, type_id, value, names),
+        },
+        _ => write_element(data, type_id, value, names),
+    }
+}
+
+/// A key, value or element of a map or set.
+fn write_container_element(
+    file: &mut dyn Writer,
+    type_id: usize,
+    value: &Value,
+    names: &mut NameWriter,
+) -> Result<()> {
+    let n = names.names;
+    match value {
+        Value::Bool(v) if type_id == n.bool_property => file.write_u8(*v as u8),
+        Value::Byte(v) if type_id == n.byte_property => file.write_u8(*v),
+        Value::Properties(properties) if type_id == n.struct_property => {
+            properties.write(file, names)
+        }
+        _ => write_element(file, type_id, value, names),
+    }
+}
+
+/// Values that are written the same way on their own and in an array.
+fn write_element(
+    file: &mut dyn Writer,
+    type_id: usize,
+    value: &Value,
+    names: &mut NameWriter,
+) -> Result<()> {
+    let n = names.names;
+    match value {
+        Value::Double(v) if type_id == n.double_property => file.write_f64(*v),
+        Value::Float(v) if type_id == n.float_property => file.write_f32(*v),
+        Value::Int16(v) if type_id == n.int16_property => file.write_i16(*v),
+        Value::Int8(v) if type_id == n.int8_property => file.write_i8(*v),
+        Value::Int(v) if type_id == n.int_property => file.write_i32(*v),
+        Value::Name(v) if type_id == n.name_property => names.write(file, v),
+        Value::Int(v) if type_id == n.object_property => {
+            file.write_i32(0)?;
+            file.write_i32(*v)
+        }
+        Value::Name(v) if type_id == n.object_property => {
+            file.write_i32(1)?;
+            names.write(file, v)
+        }
+        Value::String(v) if type_id == n.str_property => file.write_str(v),
+        Value::Text(v) if type_id == n.text_property => write_text(file, v, names),
+        Value::UInt16(v) if type_id == n.uint16_property => file.write_u16(*v),
+        Value::UInt32(v) if type_id == n.uint32_property => file.write_u32(*v),
+        Value::UInt64(v) if type_id == n.uint64_property => file.write_u64(*v),
+        Value::Unknown { type_name, raw } if type_name.id == type_id => file.write_bytes(raw),
+        _ => Err(mismatch(names, type_id, value)),
+    }
+}
+
+fn write_text(file: &mut dyn Writer, text: &Text, names: &NameWriter) -> Result<()> {
+    file.write_u32(text.flags)?;
+    file.write_i8(text.history_type())?;
+    match &text.history {
+        TextHistory::None(invariant) => match invariant {
+            Some(v) if names.names.asa => {
+                file.write_i32(1)?;
+                file.write_str(v)
+            }
+            None if names.names.asa => file.write_i32(0),
+            _ => Ok(()),
+        },
+        TextHistory::Base {
+            namespace,
+            key,
+            source,
+        } => {
+            file.write_str(namespace)?;
+            file.write_str(key)?;
+            file.write_str(source)
+        }
+        TextHistory::Format {
+            history_type,
+            pattern,
+            arguments,
+        } => {
+            write_text(file, pattern, names)?;
+            file.write_i32(arguments.len() as i32)?;
+            for (name, argument) in arguments {
+                if *history_type != Text::HISTORY_ORDERED_FORMAT {
+                    file.write_str(name)?;
+                }
+                match argument {
+                    FormatArgument::Int(v) => {
+                        file.write_i8(0)?;
+                        file.write_i64(*v)?;
+                    }
+                    FormatArgument::UInt(v) => {
+                        file.write_i8(1)?;
+                        file.write_u64(*v)?;
+                    }
+                    FormatArgument::Float(v) => {
+                        file.write_i8(2)?;
+                        file.write_f32(*v)?;
+                    }
+                    FormatArgument::Double(v) => {
+                        file.write_i8(3)?;
+                        file.write_f64(*v)?;
+                    }
+                    FormatArgument::Text(v) => {
+                        file.write_i8(4)?;
+                        write_text(file, v, names)?;
+                    }
+                    FormatArgument::Gender(v) => {
+                        file.write_i8(5)?;
+                        file.write_u8(*v)?;
+                    }
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+macro_rules! arr {
+    ($file:ident, $items:ident, $method:ident) => {{
+        $file.write_i32($items.len() as i32)?;
+        for item in $items {
+            $file.$method(*item)?;
+        }
+        Ok(())
+    }};
+}
+
+fn write_array(
+    file: &mut dyn Writer,
+    type_id: usize,
+    struct_type: Option<Name>,
+    value: &Value,
+    names: &mut NameWriter,
+) -> Result<()> {
+    let n = names.names;
+    match value {
+        Value::ArrayOfObject(items) if type_id == n.object_property => {
+            file.write_i32(items.len() as i32)?;
+            for item in items {
+                write_element(file, type_id, item, names)?;
+            }
+            Ok(())
+        }
+        Value::ArrayOfStruct(items) if type_id == n.struct_property => {
+            file.write_i32(items.len() as i32)?;
+            for item in items {
+                // Arrays added by editing have no struct type, so go by the value
+                let struct_type_id = match (struct_type, item) {
+                    (Some(struct_type), _) => struct_type.id,
+                    (None, Value::Vector(..)) => n.vector_property,
+                    (None, Value::RGBA(..)) => n.linear_color_property,
+                    (None, Value::Guid(..)) => n.guid_property,
+                    _ => n.struct_property,
+                };
+                write_struct_value(file, struct_type_id, item, names)?;
+            }
+            Ok(())
+        }
+        Value::ArrayOfU32(items) if type_id == n.uint32_property => arr!(file, items, write_u32),
+        Value::ArrayOfI32(items) if type_id == n.int_property => arr!(file, items, write_i32),
+        Value::ArrayOfU16(items) if type_id == n.uint16_property => arr!(file, items, write_u16),
+        Value::ArrayOfI16(items) if type_id == n.int16_property => arr!(file, items, write_i16),
+        Value::ArrayOfU8(items) if type_id == n.byte_property => arr!(file, items, write_u8),
+        Value::ArrayOfI8(items) if type_id == n.int8_property => arr!(file, items, write_i8),
+        Value::ArrayOfU64(items) if type_id == n.uint64_property => arr!(file, items, write_u64),
+        Value::ArrayOfF32(items) if type_id == n.float_property => arr!(file, items, write_f32),
+        Value::ArrayOfF64(items) if type_id == n.double_property => arr!(file, items, write_f64),
+        Value::ArrayOfStr(items) if type_id == n.str_property => {
+            file.write_i32(items.len() as i32)?;
+            for item in items {
+                file.write_str(item)?;
+            }
+            Ok(())
+        }
+        Value::ArrayOfBool(items) if type_id == n.bool_property => {
+            file.write_i32(items.len() as i32)?;
+            for item in items {
+                file.write_u8(*item as u8)?;
+            }
+            Ok(())
+        }
+        Value::Unknown { type_name, raw } if type_name.id == type_id => file.write_bytes(raw),
+        Value::ArrayOfName(items) if type_id == n.name_property || type_id == n.byte_property => {
+            file.write_i32(items.len() as i32)?;
+            for item in items {
+                names.write(file, item)?;
+            }
+            Ok(())
+        }
+        _ => Err(mismatch(names, type_id, value)),
+    }
+}
+
+fn write_struct_value(
+    file: &mut dyn Writer,
+    struct_type_id: usize,
+    value: &Value,
+    names: &mut NameWriter,
+) -> Result<()> {
+    let n = names.names;
+    match value {
+        Value::Vector(x, y, z) => {
+            file.write_f32(*x)?;
+            file.write_f32(*y)?;
+            file.write_f32(*z)
+        }
+        Value::Vector2D(x, y) => {
+            file.write_f32(*x)?;
+            file.write_f32(*y)
+        }
+        Value::Quat(x, y, z, w) => {
+            file.write_f32(*x)?;
+            file.write_f32(*y)?;
+            file.write_f32(*z)?;
+            file.write_f32(*w)
+        }
+        Value::RGBA(r, g, b, a) if struct_type_id == n.color_property => {
+            for channel in &[b, g, r, a] {
+                file.write_u8((**channel * 255.0).round() as u8)?;
+            }
+            Ok(())
+        }
+        Value::RGBA(r, g, b, a) => {
+            file.write_f32(*r)?;
+            file.write_f32(*g)?;
+            file.write_f32(*b)?;
+            file.write_f32(*a)
+        }
+        Value::Rotator(pitch, yaw, roll) => {
+            file.write_f32(*pitch)?;
+            file.write_f32(*yaw)?;
+            file.write_f32(*roll)
+        }
+        Value::IntPoint(x, y) => {
+            file.write_i32(*x)?;
+            file.write_i32(*y)
+        }
+        Value::IntVector(x, y, z) => {
+            file.write_i32(*x)?;
+            file.write_i32(*y)?;
+            file.write_i32(*z)
+        }
+        Value::Guid(v) if struct_type_id == n.guid_property => file.write_u128(*v),
+        Value::Box { min, max, valid } => {
+            write_vector(file, min)?;
+            write_vector(file, max)?;
+            file.write_u8(*valid as u8)
+        }
+        Value::Transform {
+            rotation: (x, y, z, w),
+            translation,
+            scale,
+        } => {
+            file.write_f32(*x)?;
+            file.write_f32(*y)?;
+            file.write_f32(*z)?;
+            file.write_f32(*w)?;
+            write_vector(file, translation)?;
+            write_vector(file, scale)
+        }
+        Value::UniqueNetId { size, id, .. } => {
+            file.write_i32(*size)?;
+            file.write_str(id)
+        }
+        Value::ItemNetId(id1, id2) if struct_type_id == n.item_netid_property => {
+            file.write_u32(*id1)?;
+            file.write_u32(*id2)
+        }
+        Value::Properties(properties) => properties.write(file, names),
+        _ => Err(mismatch(names, struct_type_id, value)),
+    }
+}
+
+fn write_vector(file: &mut dyn Writer, (x, y, z): &(f32, f32, f32)) -> Result<()> {
+    file.write_f32(*x)?;
+    file.write_f32(*y)?;
+    file.write_f32(*z)
+}
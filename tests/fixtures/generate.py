@@ -0,0 +1,139 @@
+#!/usr/bin/env python3
+"""Writes the SQLite test fixtures in this directory.
+
+    python3 tests/fixtures/generate.py
+
+items.sqlite is a plain table for the SQLite reader, with small pages so the
+table has interior pages and a row that overflows onto another page.
+
+asa.ark follows the layout read by src/file/asa.rs: a SaveHeader with its
+names table keyed by arbitrary ids, an ActorTransforms row, and objects in
+the game table with Unreal Engine 5 property tags. It is built from that
+description rather than copied from a save written by the game.
+"""
+import os
+import sqlite3
+import struct
+
+HERE = os.path.dirname(os.path.abspath(__file__))
+
+
+def connect(name):
+    path = os.path.join(HERE, name)
+    if os.path.exists(path):
+        os.remove(path)
+    db = sqlite3.connect(path)
+    db.execute("PRAGMA page_size=512")
+    db.execute("PRAGMA journal_mode=DELETE")
+    return db
+
+
+def items():
+    db = connect("items.sqlite")
+    db.execute("CREATE TABLE items (id INTEGER PRIMARY KEY, name TEXT, data BLOB, weight REAL)")
+    for i in range(1, 41):
+        data = bytes(range(256)) * 8 if i == 20 else bytes([i]) * i
+        weight = None if i % 2 else i + 0.25
+        db.execute("INSERT INTO items VALUES (?,?,?,?)", (i, "Item %d" % i, data, weight))
+    db.commit()
+    db.close()
+
+
+def i16(v):
+    return struct.pack("<h", v)
+
+
+def i32(v):
+    return struct.pack("<i", v)
+
+
+def u32(v):
+    return struct.pack("<I", v)
+
+
+def f64(v):
+    return struct.pack("<d", v)
+
+
+def fstring(s):
+    data = s.encode("utf-8") + b"\0"
+    return i32(len(data)) + data
+
+
+NAMES = [
+    "None",
+    "Dodo_Character_BP_C",
+    "DinoCharacterStatusComponent_BP_C",
+    "IntProperty",
+    "ObjectProperty",
+    "StructProperty",
+    "ArrayProperty",
+    "MyCharacterStatusComponent",
+    "Owner",
+    "DinoOwner",
+    "Target",
+    "Targets",
+    "TargetingTeam",
+]
+# Names are saved as ids into the header's names table, which needn't be
+# consecutive
+NAME_IDS = {name: 100 + i * 3 for i, name in enumerate(NAMES)}
+
+
+def name(n, instance=0):
+    return u32(NAME_IDS[n]) + u32(instance)
+
+
+def prop(n, type_name, tag, data):
+    """A property with an Unreal Engine 5 tag: the type's inner names, then
+    a byte flagging a property guid, which these don't have."""
+    return name(n) + name(type_name) + i32(len(data)) + i32(0) + tag + b"\0" + data
+
+
+def object_ref(guid):
+    return i16(0) + guid
+
+
+def game_object(class_name, properties):
+    return name(class_name) + i32(0) + i32(0) + i32(0) + i32(0) + properties
+
+
+def asa():
+    dodo = bytes(range(1, 17))
+    status = bytes(range(17, 33))
+    end = name("None")
+    owner = prop("Target", "ObjectProperty", b"", object_ref(status)) + end
+    targets = i32(2) + object_ref(status) + object_ref(dodo)
+    dodo_properties = (
+        prop("TargetingTeam", "IntProperty", b"", i32(7))
+        + prop("MyCharacterStatusComponent", "ObjectProperty", b"", object_ref(status))
+        + prop("Owner", "StructProperty", name("DinoOwner") + bytes(16), owner)
+        + prop("Targets", "ArrayProperty", name("ObjectProperty"), targets)
+        + end
+    )
+
+    version = i16(13)
+    game_time = f64(4321.5)
+    parts = i32(1) + fstring("TheIsland_WP") + i32(0)
+    names = i32(len(NAMES)) + b"".join(u32(NAME_IDS[n]) + fstring(n) for n in NAMES)
+    names_offset = len(version + i32(0) + i32(0) + game_time + parts)
+    header = version + i32(names_offset) + i32(0) + game_time + parts + names
+
+    # Guid, location, rotation and an unused value, ended by a zero guid
+    location = [100.5, -200.25, 300.0, 10.0, 90.0, 0.0, 1.0]
+    transforms = dodo + b"".join(f64(v) for v in location) + bytes(16)
+
+    db = connect("asa.ark")
+    db.execute("CREATE TABLE game (key BLOB PRIMARY KEY, value BLOB)")
+    db.execute("CREATE TABLE custom (key TEXT PRIMARY KEY, value BLOB)")
+    db.execute("INSERT INTO custom VALUES (?,?)", ("SaveHeader", header))
+    db.execute("INSERT INTO custom VALUES (?,?)", ("ActorTransforms", transforms))
+    db.execute("INSERT INTO game VALUES (?,?)", (dodo, game_object("Dodo_Character_BP_C", dodo_properties)))
+    db.execute("INSERT INTO game VALUES (?,?)", (status, game_object("DinoCharacterStatusComponent_BP_C", end)))
+    db.commit()
+    db.close()
+
+
+if __name__ == "__main__":
+    items()
+    asa()